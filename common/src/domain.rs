@@ -3,8 +3,10 @@ use ark_poly::univariate::DensePolynomial;
 use ark_poly::{
     DenseUVPolynomial, EvaluationDomain, Evaluations, GeneralEvaluationDomain, Polynomial,
 };
+use ark_std::rand::Rng;
 use ark_std::{vec, vec::Vec};
 
+use crate::seed::SeedSource;
 use crate::FieldColumn;
 
 pub const ZK_ROWS: usize = 3;
@@ -25,6 +27,12 @@ impl<F: FftField> Domains<F> {
         Self { x1, x4 }
     }
 
+    fn new_checked(n: usize) -> Option<Self> {
+        let x1 = GeneralEvaluationDomain::<F>::new(n)?;
+        let x4 = GeneralEvaluationDomain::<F>::new(4 * n)?;
+        Some(Self { x1, x4 })
+    }
+
     fn column_from_evals(&self, evals: Vec<F>, len: usize) -> FieldColumn<F> {
         assert_eq!(evals.len(), self.x1.size());
         let evals = Evaluations::from_vec_and_domain(evals, self.x1);
@@ -70,9 +78,26 @@ pub struct Domain<F: FftField> {
 
 impl<F: FftField> Domain<F> {
     pub fn new(n: usize, hiding: bool) -> Self {
-        let domains = Domains::new(n);
+        Self::from_domains(Domains::new(n), hiding)
+    }
+
+    // Same as `new`, but returns `None` if the field doesn't have FFT domains of the required sizes,
+    // or if the domain is too small to fit the zero-knowledge rows.
+    pub fn new_checked(n: usize, hiding: bool) -> Option<Self> {
+        let domains = Domains::new_checked(n)?;
+        if hiding && domains.x1.size() <= ZK_ROWS {
+            return None;
+        }
+        Some(Self::from_domains(domains, hiding))
+    }
+
+    fn from_domains(domains: Domains<F>, hiding: bool) -> Self {
         let size = domains.x1.size();
-        let capacity = if hiding { size - ZK_ROWS } else { size };
+        let capacity = if hiding {
+            Self::capacity_for_hiding(size)
+        } else {
+            Self::capacity_for_no_hiding(size)
+        };
         let last_row_index = capacity - 1;
 
         let l_first = l_i(0, size);
@@ -95,6 +120,50 @@ impl<F: FftField> Domain<F> {
         }
     }
 
+    // Number of rows available for the columns in a hiding domain of the given (FFT) size,
+    // the last `ZK_ROWS` rows are reserved for the blinding.
+    pub fn capacity_for_hiding(size: usize) -> usize {
+        size - ZK_ROWS
+    }
+
+    // Number of rows available for the columns in a non-hiding domain of the given (FFT) size.
+    pub fn capacity_for_no_hiding(size: usize) -> usize {
+        size
+    }
+
+    // Same FFT domain with the other hiding mode. Only the polynomials that depend on the capacity are recomputed.
+    pub fn with_hiding(self, hiding: bool) -> Self {
+        if hiding == self.hiding {
+            return self;
+        }
+        let size = self.domains.x1.size();
+        let capacity = if hiding {
+            Self::capacity_for_hiding(size)
+        } else {
+            Self::capacity_for_no_hiding(size)
+        };
+        let last_row_index = capacity - 1;
+
+        let mut l_first = self.l_first;
+        l_first.len = capacity;
+        let l_last = l_i(last_row_index, size);
+        let l_last = self.domains.column_from_evals(l_last, capacity);
+        let not_last_row = vanishes_on_row(last_row_index, self.domains.x1);
+        let not_last_row = self.domains.column_from_poly(not_last_row, capacity);
+
+        let zk_rows_vanishing_poly = hiding.then(|| vanishes_on_last_3_rows(self.domains.x1));
+
+        Self {
+            domains: self.domains,
+            hiding,
+            capacity,
+            not_last_row,
+            l_first,
+            l_last,
+            zk_rows_vanishing_poly,
+        }
+    }
+
     pub(crate) fn divide_by_vanishing_poly(&self, poly: &DensePolynomial<F>) -> DensePolynomial<F> {
         let (quotient, remainder) = if self.hiding {
             let exclude_zk_rows = poly * self.zk_rows_vanishing_poly.as_ref().unwrap();
@@ -106,24 +175,73 @@ impl<F: FftField> Domain<F> {
         quotient
     }
 
-    pub(crate) fn column(&self, mut evals: Vec<F>, hidden: bool) -> FieldColumn<F> {
+    pub(crate) fn column(&self, evals: Vec<F>, hidden: bool) -> FieldColumn<F> {
+        let rng = &mut getrandom_or_panic::getrandom_or_panic();
+        self.column_with_rng(evals, hidden, rng)
+    }
+
+    pub(crate) fn column_with_rng<R: Rng>(
+        &self,
+        evals: Vec<F>,
+        hidden: bool,
+        rng: &mut R,
+    ) -> FieldColumn<F> {
         let len = evals.len();
-        assert!(len <= self.capacity);
+        let evals = self.padded_evals(evals, hidden, rng);
+        self.domains.column_from_evals(evals, len)
+    }
+
+    // Same as `column_with_rng` for 2 columns, that are interpolated concurrently with the `parallel` feature.
+    // The blinding values are drawn from `rng` in the same order either way.
+    pub(crate) fn column_pair_with_rng<R: Rng>(
+        &self,
+        evals: (Vec<F>, Vec<F>),
+        hidden: bool,
+        rng: &mut R,
+    ) -> (FieldColumn<F>, FieldColumn<F>) {
+        let lens = (evals.0.len(), evals.1.len());
+        let evals_0 = self.padded_evals(evals.0, hidden, rng);
+        let evals_1 = self.padded_evals(evals.1, hidden, rng);
+        let interpolate_0 = || self.domains.column_from_evals(evals_0, lens.0);
+        let interpolate_1 = || self.domains.column_from_evals(evals_1, lens.1);
+        #[cfg(feature = "parallel")]
+        let (column_0, column_1) = rayon::join(interpolate_0, interpolate_1);
+        #[cfg(not(feature = "parallel"))]
+        let (column_0, column_1) = (interpolate_0(), interpolate_1());
+        (column_0, column_1)
+    }
+
+    // Pads the values to the domain size, with random values in the zero-knowledge rows, if the column is hidden.
+    fn padded_evals<R: Rng>(&self, mut evals: Vec<F>, hidden: bool, rng: &mut R) -> Vec<F> {
+        assert!(evals.len() <= self.capacity);
         if self.hiding && hidden {
             evals.resize(self.capacity, F::zero());
-            evals.resize_with(self.domains.x1.size(), || {
-                F::rand(&mut getrandom_or_panic::getrandom_or_panic())
-            });
+            evals.resize_with(self.domains.x1.size(), || F::rand(rng));
         } else {
             evals.resize(self.domains.x1.size(), F::zero());
         }
-        self.domains.column_from_evals(evals, len)
+        evals
     }
 
     pub fn private_column(&self, evals: Vec<F>) -> FieldColumn<F> {
         self.column(evals, true)
     }
 
+    // Private column blinded with the randomness derived from the `seed_source`,
+    // for the environments where the system randomness is not available.
+    pub fn private_column_with_seed_source(
+        &self,
+        evals: Vec<F>,
+        seed_source: &dyn SeedSource,
+    ) -> FieldColumn<F> {
+        self.column_with_rng(evals, true, &mut seed_source.to_rng())
+    }
+
+    // Private column blinded with the randomness supplied by the caller.
+    pub fn private_column_with_rng<R: Rng>(&self, evals: Vec<F>, rng: &mut R) -> FieldColumn<F> {
+        self.column_with_rng(evals, true, rng)
+    }
+
     // public column
     pub fn public_column(&self, evals: Vec<F>) -> FieldColumn<F> {
         self.column(evals, false)
@@ -133,9 +251,38 @@ impl<F: FftField> Domain<F> {
         self.domains.x1.group_gen()
     }
 
+    // Evaluates the domain-defined polynomials in `z`, consistently with the hiding mode of the domain.
+    pub fn evaluate(&self, z: F) -> EvaluatedDomain<F> {
+        EvaluatedDomain::from_params(&self.parameters(), z)
+    }
+
+    // Evaluates `not_last_row = X - w^{capacity-1}` in `z` in constant time.
+    pub fn evaluate_not_last_at(&self, z: F) -> F {
+        z - self.omega().pow([(self.capacity - 1) as u64])
+    }
+
+    // The Lagrange basis polynomial `L_i`, that is `1` on the `i`-th row and `0` on the others.
+    pub fn lagrange_poly(&self, i: usize) -> DensePolynomial<F> {
+        if i == 0 {
+            self.l_first.poly.clone()
+        } else if i == self.capacity - 1 {
+            self.l_last.poly.clone()
+        } else {
+            let l_i = l_i(i, self.domains.x1.size());
+            self.domains.column_from_evals(l_i, self.capacity).poly
+        }
+    }
+
     pub fn domain(&self) -> GeneralEvaluationDomain<F> {
         self.domains.x1
     }
+
+    pub fn parameters(&self) -> DomainParameters<F> {
+        DomainParameters {
+            domain: self.domains.x1,
+            hiding: self.hiding,
+        }
+    }
 }
 
 fn l_i<F: FftField>(i: usize, n: usize) -> Vec<F> {
@@ -151,7 +298,7 @@ fn vanishes_on_row<F: FftField>(
 ) -> DensePolynomial<F> {
     assert!(i < domain.size());
     let w = domain.group_gen();
-    let wi = w.pow(&[i as u64]);
+    let wi = w.pow([i as u64]);
     let wi = DensePolynomial::from_coefficients_slice(&[wi]);
     let x = DensePolynomial::from_coefficients_slice(&[F::zero(), F::one()]);
     &x - &wi
@@ -161,7 +308,7 @@ fn vanishes_on_row<F: FftField>(
 fn vanishes_on_last_3_rows<F: FftField>(domain: GeneralEvaluationDomain<F>) -> DensePolynomial<F> {
     let w = domain.group_gen();
     let n3 = (domain.size() - ZK_ROWS) as u64;
-    let w3 = w.pow(&[n3]);
+    let w3 = w.pow([n3]);
     let w2 = w3 * w;
     let w1 = w2 * w;
     assert_eq!(w1, domain.group_gen_inv());
@@ -170,6 +317,13 @@ fn vanishes_on_last_3_rows<F: FftField>(domain: GeneralEvaluationDomain<F>) -> D
     &(&(&x - &c(w3)) * &(&x - &c(w2))) * &(&x - &c(w1))
 }
 
+// Everything needed to evaluate the domain-defined polynomials in a point.
+#[derive(Clone, Copy)]
+pub struct DomainParameters<F: FftField> {
+    pub domain: GeneralEvaluationDomain<F>,
+    pub hiding: bool,
+}
+
 pub struct EvaluatedDomain<F: FftField> {
     pub domain: GeneralEvaluationDomain<F>,
     pub not_last_row: F,
@@ -180,6 +334,11 @@ pub struct EvaluatedDomain<F: FftField> {
 
 impl<F: FftField> EvaluatedDomain<F> {
     pub fn new(domain: GeneralEvaluationDomain<F>, z: F, hiding: bool) -> Self {
+        Self::from_params(&DomainParameters { domain, hiding }, z)
+    }
+
+    pub fn from_params(params: &DomainParameters<F>, z: F) -> Self {
+        let DomainParameters { domain, hiding } = *params;
         let k = if hiding { ZK_ROWS } else { 0 };
         let mut z_n = z; // z^n, n=2^d - domain size, so squarings only
         for _ in 0..domain.log_size_of_group() {
@@ -230,10 +389,13 @@ impl<F: FftField> EvaluatedDomain<F> {
 #[cfg(test)]
 mod tests {
     use ark_ed_on_bls12_381_bandersnatch::Fq;
+    use ark_ff::{FftField, Field, One, Zero};
     use ark_poly::Polynomial;
     use ark_std::{test_rng, UniformRand};
 
     use crate::domain::{Domain, EvaluatedDomain};
+    use crate::seed::DeterministicSeedSource;
+    use crate::test_helpers::random_vec;
 
     fn _test_evaluated_domain(hiding: bool) {
         let rng = &mut test_rng();
@@ -249,6 +411,11 @@ mod tests {
             domain.not_last_row.poly.evaluate(&z),
             domain_eval.not_last_row
         );
+        assert_eq!(domain.evaluate_not_last_at(z), domain_eval.not_last_row);
+        let same_domain_eval = domain.evaluate(z);
+        assert_eq!(same_domain_eval.l_first, domain_eval.l_first);
+        assert_eq!(same_domain_eval.l_last, domain_eval.l_last);
+        assert_eq!(same_domain_eval.not_last_row, domain_eval.not_last_row);
     }
 
     #[test]
@@ -256,4 +423,69 @@ mod tests {
         _test_evaluated_domain(false);
         _test_evaluated_domain(true);
     }
+
+    #[test]
+    fn test_with_hiding() {
+        for hiding in [false, true] {
+            let domain = Domain::<Fq>::new(1024, hiding).with_hiding(!hiding);
+            let expected = Domain::<Fq>::new(1024, !hiding);
+            assert_eq!(domain.hiding, expected.hiding);
+            assert_eq!(domain.capacity, expected.capacity);
+            assert_eq!(domain.l_first.len, expected.l_first.len);
+            assert_eq!(domain.l_first.poly, expected.l_first.poly);
+            assert_eq!(domain.l_last.poly, expected.l_last.poly);
+            assert_eq!(domain.not_last_row.poly, expected.not_last_row.poly);
+            assert_eq!(
+                domain.zk_rows_vanishing_poly,
+                expected.zk_rows_vanishing_poly
+            );
+        }
+    }
+
+    #[test]
+    fn test_new_checked() {
+        assert!(Domain::<Fq>::new_checked(1024, true).is_some());
+        assert!(Domain::<Fq>::new_checked(2, true).is_none());
+        assert!(Domain::<Fq>::new_checked(1 << Fq::TWO_ADICITY, false).is_none());
+    }
+
+    #[test]
+    fn test_capacity() {
+        assert_eq!(
+            Domain::<Fq>::new(1024, true).capacity,
+            Domain::<Fq>::capacity_for_hiding(1024)
+        );
+        assert_eq!(
+            Domain::<Fq>::new(1024, false).capacity,
+            Domain::<Fq>::capacity_for_no_hiding(1024)
+        );
+    }
+
+    #[test]
+    fn test_lagrange_poly() {
+        let domain = Domain::<Fq>::new(1024, true);
+        let w = domain.omega();
+        for i in [0, 1, 512, domain.capacity - 1, 1023] {
+            let l_i = domain.lagrange_poly(i);
+            assert_eq!(l_i.evaluate(&w.pow([i as u64])), Fq::one());
+            assert!(l_i.evaluate(&w.pow([(i as u64 + 1) % 1024])).is_zero());
+        }
+    }
+
+    #[test]
+    fn test_deterministic_seed_source() {
+        let rng = &mut test_rng();
+
+        let domain = Domain::<Fq>::new(1024, true);
+        let evals = random_vec(domain.capacity, rng);
+        let col1 = domain
+            .private_column_with_seed_source(evals.clone(), &DeterministicSeedSource(b"seed"));
+        let col2 = domain
+            .private_column_with_seed_source(evals.clone(), &DeterministicSeedSource(b"seed"));
+        let col3 =
+            domain.private_column_with_seed_source(evals, &DeterministicSeedSource(b"other"));
+        assert_eq!(col1.poly, col2.poly);
+        assert_ne!(col1.poly, col3.poly);
+        assert_eq!(col1.constrained_evals(), col3.constrained_evals());
+    }
 }
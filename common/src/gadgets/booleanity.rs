@@ -1,6 +1,7 @@
 use ark_ff::{FftField, Field, Zero};
 use ark_poly::univariate::DensePolynomial;
 use ark_poly::{Evaluations, GeneralEvaluationDomain};
+use ark_std::rand::Rng;
 use ark_std::{vec, vec::Vec};
 
 use crate::domain::Domain;
@@ -15,13 +16,57 @@ pub struct BitColumn<F: FftField> {
 
 impl<F: FftField> BitColumn<F> {
     pub fn init(bits: Vec<bool>, domain: &Domain<F>) -> Self {
+        Self::init_with_rng(bits, domain, &mut getrandom_or_panic::getrandom_or_panic())
+    }
+
+    // Same as `init`, but the column is blinded with the randomness supplied by the caller.
+    pub fn init_with_rng<R: Rng>(bits: Vec<bool>, domain: &Domain<F>, rng: &mut R) -> Self {
         let bits_as_field_elements = bits
             .iter()
             .map(|&b| if b { F::one() } else { F::zero() })
             .collect();
-        let col = domain.private_column(bits_as_field_elements);
+        let col = domain.private_column_with_rng(bits_as_field_elements, rng);
         Self { bits, col }
     }
+
+    // Pointwise XOR of the bit columns of the same length.
+    // The result is well-formed, but that should still be enforced with a `Booleanity` constraint.
+    pub fn xor(a: &BitColumn<F>, b: &BitColumn<F>, domain: &Domain<F>) -> Self {
+        assert_eq!(a.len(), b.len());
+        let bits = a.iter().zip(b.iter()).map(|(a, b)| a ^ b).collect();
+        Self::init(bits, domain)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = bool> + '_ {
+        self.bits.iter().copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.bits.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bits.is_empty()
+    }
+
+    pub fn as_field_column(&self) -> &FieldColumn<F> {
+        &self.col
+    }
+
+    pub fn into_field_column(self) -> FieldColumn<F> {
+        self.col
+    }
+
+    pub fn is_valid(&self) -> bool {
+        is_bitmask(&self.col)
+    }
+}
+
+// Checks that the constrained part of the column consists of 0s and 1s.
+pub fn is_bitmask<F: FftField>(col: &FieldColumn<F>) -> bool {
+    col.constrained_evals()
+        .iter()
+        .all(|x| x.is_zero() || x.is_one())
 }
 
 impl<F: FftField> Column<F> for BitColumn<F> {
@@ -42,11 +87,39 @@ pub struct Booleanity<F: FftField> {
     bits: BitColumn<F>,
 }
 
-impl<'a, F: FftField> Booleanity<F> {
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BooleanityError<F> {
+    NonBinaryValue { index: usize, value: F },
+}
+
+impl<F: FftField> Booleanity<F> {
     pub fn init(bits: BitColumn<F>) -> Self {
         Self { bits }
     }
 
+    // For the columns that weren't built with `BitColumn::init`: checks that the constrained part is binary.
+    pub fn init_checked(
+        col: &FieldColumn<F>,
+        domain: &Domain<F>,
+    ) -> Result<Self, BooleanityError<F>> {
+        assert!(col.len <= domain.capacity);
+        let bits = col
+            .constrained_evals()
+            .iter()
+            .enumerate()
+            .map(|(index, &value)| match value {
+                v if v.is_zero() => Ok(false),
+                v if v.is_one() => Ok(true),
+                _ => Err(BooleanityError::NonBinaryValue { index, value }),
+            })
+            .collect::<Result<_, _>>()?;
+        let bits = BitColumn {
+            bits,
+            col: col.clone(),
+        };
+        Ok(Self { bits })
+    }
+
     pub fn constraints(&self) -> Vec<Evaluations<F>> {
         let mut c = const_evals(F::one(), self.bits.domain_4x()); // c = 1
         let b = &self.bits.col.evals_4x;
@@ -55,6 +128,10 @@ impl<'a, F: FftField> Booleanity<F> {
         vec![c]
     }
 
+    pub fn constraint_labels(&self) -> &'static [&'static str] {
+        &["booleanity"]
+    }
+
     pub fn constraints_linearized(&self, _z: &F) -> Vec<DensePolynomial<F>> {
         vec![DensePolynomial::zero()]
     }
@@ -70,3 +147,50 @@ impl<F: Field> VerifierGadget<F> for BooleanityValues<F> {
         vec![c]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use ark_ed_on_bls12_381_bandersnatch::Fq;
+    use ark_std::test_rng;
+
+    use crate::test_helpers::random_bitvec;
+
+    use super::*;
+
+    #[test]
+    fn test_init_checked() {
+        let rng = &mut test_rng();
+        let domain = Domain::<Fq>::new(1024, true);
+        let bits = BitColumn::init(random_bitvec(domain.capacity - 1, 0.5, rng), &domain);
+        assert!(Booleanity::init_checked(bits.as_field_column(), &domain).is_ok());
+
+        let mut vals = bits.as_field_column().constrained_evals().to_vec();
+        vals[10] = Fq::from(2u8);
+        let col = domain.private_column(vals);
+        assert_eq!(
+            Booleanity::init_checked(&col, &domain).err(),
+            Some(BooleanityError::NonBinaryValue {
+                index: 10,
+                value: Fq::from(2u8)
+            })
+        );
+    }
+
+    #[test]
+    fn test_xor() {
+        let rng = &mut test_rng();
+        let domain = Domain::<Fq>::new(1024, true);
+        let n = domain.capacity - 1;
+        let a = BitColumn::init(random_bitvec(n, 0.5, rng), &domain);
+        let b = BitColumn::init(random_bitvec(n, 0.5, rng), &domain);
+        let c = BitColumn::xor(&a, &b, &domain);
+        assert!(c.is_valid());
+        for i in 0..n {
+            assert_eq!(c.bits[i], a.bits[i] != b.bits[i]);
+            assert_eq!(
+                c.as_field_column().constrained_evals()[i],
+                Fq::from(c.bits[i])
+            );
+        }
+    }
+}
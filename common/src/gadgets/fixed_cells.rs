@@ -3,7 +3,8 @@ use ark_poly::univariate::DensePolynomial;
 use ark_poly::Evaluations;
 use ark_std::{vec, vec::Vec};
 
-use crate::domain::Domain;
+use crate::domain::{Domain, EvaluatedDomain};
+use crate::gadgets::inner_prod::InnerProd;
 use crate::gadgets::VerifierGadget;
 use crate::{const_evals, Column, FieldColumn};
 
@@ -13,6 +14,8 @@ pub struct FixedCells<F: FftField> {
     col_last: F,
     l_first: FieldColumn<F>,
     l_last: FieldColumn<F>,
+    // If not set, the gadget doesn't produce any constraints.
+    pinned: bool,
 }
 
 pub struct FixedCellsValues<F: Field> {
@@ -36,26 +39,89 @@ impl<F: FftField> FixedCells<F> {
             col_last,
             l_first,
             l_last,
+            pinned: true,
         }
     }
 
+    // A placeholder for the composition of gadgets, where the cells to fix are not known:
+    // neither constrains anything, nor contributes to the linearization polynomial.
+    pub fn unconstrained(domain: &Domain<F>) -> Self {
+        Self {
+            col: domain.public_column(vec![]),
+            col_first: F::zero(),
+            col_last: F::zero(),
+            l_first: domain.l_first.clone(),
+            l_last: domain.l_last.clone(),
+            pinned: false,
+        }
+    }
+
+    // Constrains the accumulator of the inner product gadget to start from `0` and to end with the `expected` value.
+    pub fn from_inner_prod(gadget: &InnerProd<F>, expected: F, domain: &Domain<F>) -> Self {
+        assert_eq!(gadget.acc.len, domain.capacity);
+        Self {
+            col: gadget.acc.clone(),
+            col_first: F::zero(),
+            col_last: expected,
+            l_first: domain.l_first.clone(),
+            l_last: domain.l_last.clone(),
+            pinned: true,
+        }
+    }
+
+    // Constrains the cell of `col` selected by the Lagrange basis polynomial `lagrange_poly` to be `expected_val`.
+    pub fn constraint_cell(
+        col: &FieldColumn<F>,
+        lagrange_poly: &FieldColumn<F>,
+        expected_val: F,
+    ) -> Evaluations<F> {
+        let expected = &const_evals(expected_val, col.domain_4x());
+        &lagrange_poly.evals_4x * &(&col.evals_4x - expected)
+    }
+
     pub fn constraints(&self) -> Vec<Evaluations<F>> {
-        let col = &self.col;
-        let domain = col.domain_4x();
-        let first = &const_evals(self.col_first, domain);
-        let last = &const_evals(self.col_last, domain);
-        let col = &self.col.evals_4x;
-        let l_first = &self.l_first.evals_4x;
-        let l_last = &self.l_last.evals_4x;
-        let c = &(l_first * &(col - first)) + &(l_last * &(col - last));
+        if !self.pinned {
+            return vec![];
+        }
+        let c = &Self::constraint_cell(&self.col, &self.l_first, self.col_first)
+            + &Self::constraint_cell(&self.col, &self.l_last, self.col_last);
         vec![c]
     }
 
+    pub fn constraint_labels(&self) -> &'static [&'static str] {
+        if !self.pinned {
+            return &[];
+        }
+        &["fixed_cells"]
+    }
+
     pub fn constraints_linearized(&self, _z: &F) -> Vec<DensePolynomial<F>> {
+        if !self.pinned {
+            return vec![];
+        }
         vec![DensePolynomial::zero()]
     }
 }
 
+impl<F: Field> FixedCellsValues<F> {
+    pub fn new(col: F, col_first: F, col_last: F, l_first: F, l_last: F) -> Self {
+        Self {
+            col,
+            col_first,
+            col_last,
+            l_first,
+            l_last,
+        }
+    }
+}
+
+impl<F: FftField> FixedCellsValues<F> {
+    // Takes the Lagrange basis polynomials for the first and the last rows from the evaluated domain.
+    pub fn for_first_and_last(col: F, first: F, last: F, domain: &EvaluatedDomain<F>) -> Self {
+        Self::new(col, first, last, domain.l_first, domain.l_last)
+    }
+}
+
 impl<F: Field> VerifierGadget<F> for FixedCellsValues<F> {
     fn evaluate_constraints_main(&self) -> Vec<F> {
         let c =
@@ -63,3 +129,19 @@ impl<F: Field> VerifierGadget<F> for FixedCellsValues<F> {
         vec![c]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use ark_ed_on_bls12_381_bandersnatch::Fq;
+    use ark_ff::One;
+
+    use super::*;
+
+    #[test]
+    fn test_unconstrained() {
+        let domain = Domain::<Fq>::new(1024, true);
+        let gadget = FixedCells::unconstrained(&domain);
+        assert!(gadget.constraints().is_empty());
+        assert!(gadget.constraints_linearized(&Fq::one()).is_empty());
+    }
+}
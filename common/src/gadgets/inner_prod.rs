@@ -1,6 +1,7 @@
 use ark_ff::{FftField, Field};
 use ark_poly::univariate::DensePolynomial;
 use ark_poly::{Evaluations, GeneralEvaluationDomain};
+use ark_std::rand::Rng;
 use ark_std::{vec, vec::Vec};
 
 use crate::domain::Domain;
@@ -23,12 +24,22 @@ pub struct InnerProdValues<F: Field> {
 
 impl<F: FftField> InnerProd<F> {
     pub fn init(a: FieldColumn<F>, b: FieldColumn<F>, domain: &Domain<F>) -> Self {
+        Self::init_with_rng(a, b, domain, &mut getrandom_or_panic::getrandom_or_panic())
+    }
+
+    // Same as `init`, but the acc column is blinded with the randomness supplied by the caller.
+    pub fn init_with_rng<R: Rng>(
+        a: FieldColumn<F>,
+        b: FieldColumn<F>,
+        domain: &Domain<F>,
+        rng: &mut R,
+    ) -> Self {
         assert_eq!(a.len, domain.capacity - 1); // last element is not constrained
         assert_eq!(b.len, domain.capacity - 1); // last element is not constrained
-        let inner_prods = Self::partial_inner_prods(a.vals(), b.vals());
+        let inner_prods = Self::partial_inner_prods(a.constrained_evals(), b.constrained_evals());
         let mut acc = vec![F::zero()];
         acc.extend(inner_prods);
-        let acc = domain.private_column(acc);
+        let acc = domain.private_column_with_rng(acc, rng);
         Self {
             a,
             b,
@@ -37,6 +48,16 @@ impl<F: FftField> InnerProd<F> {
         }
     }
 
+    // Sum of `b[start..end]`, computed as the inner product with the selector `a`,
+    // that is public and set to `1` in the rows `start..end`, and to `0` elsewhere.
+    pub fn sum(b: FieldColumn<F>, domain: &Domain<F>, start: usize, end: usize) -> Self {
+        assert!(start <= end && end < domain.capacity);
+        let mut selector = vec![F::zero(); domain.capacity - 1];
+        selector[start..end].fill(F::one());
+        let a = domain.public_column(selector);
+        Self::init(a, b, domain)
+    }
+
     /// Returns a[0]b[0], a[0]b[0] + a[1]b[1], ..., a[0]b[0] + a[1]b[1] + ... + a[n-1]b[n-1]
     fn partial_inner_prods(a: &[F], b: &[F]) -> Vec<F> {
         assert_eq!(a.len(), b.len());
@@ -65,6 +86,10 @@ impl<F: FftField> ProverGadget<F> for InnerProd<F> {
         vec![c]
     }
 
+    fn constraint_labels(&self) -> &'static [&'static str] {
+        &["inner_prod"]
+    }
+
     fn constraints_linearized(&self, _z: &F) -> Vec<DensePolynomial<F>> {
         let c = &self.acc.poly * self.not_last.evaluate(_z);
         vec![c]
@@ -87,7 +112,7 @@ mod tests {
     use ark_ed_on_bls12_381_bandersnatch::Fq;
     use ark_ff::{Field, Zero};
     use ark_poly::Polynomial;
-    use ark_std::test_rng;
+    use ark_std::{test_rng, UniformRand};
 
     use crate::domain::Domain;
     use crate::test_helpers::random_vec;
@@ -114,6 +139,7 @@ mod tests {
 
         let gadget = InnerProd::<Fq>::init(a, b, &domain);
 
+        gadget.acc.assert_degree_at_most(n - 1);
         let acc = &gadget.acc.evals.evals;
         assert!(acc[0].is_zero());
         assert_eq!(acc[domain.capacity - 1], ab);
@@ -130,4 +156,49 @@ mod tests {
         _test_inner_prod_gadget(false);
         _test_inner_prod_gadget(true);
     }
+
+    #[test]
+    fn test_inner_prod_sum() {
+        let rng = &mut test_rng();
+        let domain = Domain::new(1024, true);
+        let b = random_vec::<Fq, _>(domain.capacity - 1, rng);
+        let sum: Fq = b[10..100].iter().sum();
+        let gadget = InnerProd::sum(domain.private_column(b), &domain, 10, 100);
+        assert_eq!(gadget.acc.evals.evals[domain.capacity - 1], sum);
+        let constraint_poly = gadget.constraints()[0].interpolate_by_ref();
+        domain.divide_by_vanishing_poly(&constraint_poly);
+    }
+
+    fn _test_inner_prod_edge_case(a: Vec<Fq>, b: Vec<Fq>, domain: &Domain<Fq>) -> Fq {
+        let a = domain.private_column(a);
+        let b = domain.private_column(b);
+        let gadget = InnerProd::<Fq>::init(a, b, domain);
+        let constraint_poly = gadget.constraints()[0].interpolate_by_ref();
+        domain.divide_by_vanishing_poly(&constraint_poly);
+        gadget.acc.evals.evals[domain.capacity - 1]
+    }
+
+    #[test]
+    fn test_inner_prod_with_zero_vector() {
+        let rng = &mut test_rng();
+        let domain = Domain::new(1024, true);
+        let a = random_vec(domain.capacity - 1, rng);
+        let b = vec![Fq::zero(); domain.capacity - 1];
+        assert!(_test_inner_prod_edge_case(a, b, &domain).is_zero());
+    }
+
+    #[test]
+    fn test_inner_prod_single_nonzero() {
+        let rng = &mut test_rng();
+        let domain = Domain::new(1024, true);
+        let i = 100;
+        let mut a = vec![Fq::zero(); domain.capacity - 1];
+        let mut b = vec![Fq::zero(); domain.capacity - 1];
+        a[i] = Fq::rand(rng);
+        b[i] = Fq::rand(rng);
+        assert_eq!(
+            _test_inner_prod_edge_case(a.clone(), b.clone(), &domain),
+            a[i] * b[i]
+        );
+    }
 }
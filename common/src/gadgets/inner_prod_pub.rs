@@ -1,40 +1,51 @@
 use ark_ff::{FftField, Field};
-use ark_poly::{Evaluations, GeneralEvaluationDomain};
 use ark_poly::univariate::DensePolynomial;
+use ark_poly::{Evaluations, GeneralEvaluationDomain};
+use ark_std::{vec, vec::Vec};
 
-use crate::{Column, const_evals, FieldColumn};
 use crate::domain::Domain;
 use crate::gadgets::{ProverGadget, VerifierGadget};
+use crate::{const_evals, Column, FieldColumn};
 
-pub struct InnerProd<F: FftField> {
+// Inner product of 2 public columns, that is a public value itself.
+// As nothing is private, the accumulator doesn't need blinding,
+// and the result is constrained without a separate `FixedCells` gadget.
+pub struct InnerProdPub<F: FftField> {
     a: FieldColumn<F>,
     b: FieldColumn<F>,
+    not_last: FieldColumn<F>,
     l_last: FieldColumn<F>,
     pub acc: FieldColumn<F>,
     pub inner_prod: F,
 }
 
-pub struct InnerProdValues<F: Field> {
+pub struct InnerProdPubValues<F: Field> {
     pub a: F,
     pub b: F,
+    pub not_last: F,
     pub l_last: F,
     pub acc: F,
     pub inner_prod: F,
 }
 
-
-impl<F: FftField> InnerProd<F> {
+impl<F: FftField> InnerProdPub<F> {
     pub fn init(a: FieldColumn<F>, b: FieldColumn<F>, domain: &Domain<F>) -> Self {
-        assert_eq!(a.evals.evals.len(), domain.capacity);
-        assert_eq!(b.evals.evals.len(), domain.capacity);
-        let l_last = domain.l_last.clone();
-        let inner_prods = Self::partial_inner_prods(&a.evals.evals, &b.evals.evals);
+        assert_eq!(a.len, domain.capacity);
+        assert_eq!(b.len, domain.capacity);
+        let inner_prods = Self::partial_inner_prods(a.constrained_evals(), b.constrained_evals());
         let (&inner_prod, partial_prods) = inner_prods.split_last().unwrap();
         // 0, a[0]b[0], a[0]b[0] + a[1]b[1], ..., a[0]b[0] + a[1]b[1] + ... + a[n-2]b[n-2]
         let mut acc = vec![F::zero()];
         acc.extend(partial_prods);
-        let acc = domain.column(acc);
-        Self { a, b, acc, l_last, inner_prod }
+        let acc = domain.public_column(acc);
+        Self {
+            a,
+            b,
+            not_last: domain.not_last_row.clone(),
+            l_last: domain.l_last.clone(),
+            acc,
+            inner_prod,
+        }
     }
 
     /// Returns a[0]b[0], a[0]b[0] + a[1]b[1], ..., a[0]b[0] + a[1]b[1] + ... + a[n-1]b[n-1]
@@ -48,100 +59,89 @@ impl<F: FftField> InnerProd<F> {
             })
             .collect()
     }
-
-    fn evaluate_assignment(&self, zeta: &F) -> InnerProdValues<F> {
-        InnerProdValues {
-            a: self.a.evaluate(zeta),
-            b: self.b.evaluate(zeta),
-            l_last: self.l_last.evaluate(zeta), //TODO: can be done in O(1)
-            acc: self.acc.evaluate(zeta),
-            inner_prod: self.inner_prod,
-        }
-    }
 }
 
-impl<F: FftField> ProverGadget<F> for InnerProd<F> {
+impl<F: FftField> ProverGadget<F> for InnerProdPub<F> {
     fn witness_columns(&self) -> Vec<DensePolynomial<F>> {
         vec![self.acc.poly.clone()]
     }
 
+    // `acc[i+1] = acc[i] + a[i]b[i]` for all rows but the last, and
+    // `acc[n-1] + a[n-1]b[n-1] = inner_prod` for the last row.
     fn constraints(&self) -> Vec<Evaluations<F>> {
-        let domain = self.l_last.domain_4x();
-        let inner_prod = &const_evals(self.inner_prod, domain);
-        let l_last = &self.l_last.evals_4x;
+        let inner_prod = &const_evals(self.inner_prod, self.acc.domain_4x());
         let a = &self.a.evals_4x;
         let b = &self.b.evals_4x;
         let acc = &self.acc.evals_4x;
         let acc_shifted = &self.acc.shifted_4x();
-        let c = &(&(acc_shifted - acc) - &(a * b)) + &(inner_prod * l_last);
+        let not_last = &self.not_last.evals_4x;
+        let l_last = &self.l_last.evals_4x;
+        let ab = &(a * b);
+        let c =
+            &(&(&(acc_shifted - acc) - ab) * not_last) + &(&(&(acc + ab) - inner_prod) * l_last);
         vec![c]
     }
 
-    fn constraints_linearized(&self, _z: &F) -> Vec<DensePolynomial<F>> {
-        let c = self.acc.as_poly();
-        vec![c.clone()]
+    fn constraint_labels(&self) -> &'static [&'static str] {
+        &["inner_prod_pub"]
+    }
+
+    fn constraints_linearized(&self, z: &F) -> Vec<DensePolynomial<F>> {
+        let c = &self.acc.poly * self.not_last.evaluate(z);
+        vec![c]
     }
 
     fn domain(&self) -> GeneralEvaluationDomain<F> {
-        self.a.domain()
+        self.a.evals.domain()
     }
 }
 
-
-impl<F: Field> VerifierGadget<F> for InnerProdValues<F> {
+impl<F: Field> VerifierGadget<F> for InnerProdPubValues<F> {
     fn evaluate_constraints_main(&self) -> Vec<F> {
-        let c = self.inner_prod * self.l_last - self.a * self.b - self.acc;
+        let ab = self.a * self.b;
+        let c = (-self.acc - ab) * self.not_last + (self.acc + ab - self.inner_prod) * self.l_last;
         vec![c]
     }
 }
 
-
 #[cfg(test)]
 mod tests {
     use ark_ed_on_bls12_381_bandersnatch::Fq;
-    use ark_ff::{Field, Zero};
-    use ark_poly::{GeneralEvaluationDomain, Polynomial};
-    use ark_poly::EvaluationDomain;
+    use ark_ff::Zero;
+    use ark_poly::Polynomial;
     use ark_std::test_rng;
 
     use crate::test_helpers::random_vec;
 
     use super::*;
 
-    fn inner_prod<F: Field>(a: &[F], b: &[F]) -> F {
-        assert_eq!(a.len(), b.len());
-        a.iter().zip(b)
-            .map(|(a, b)| *a * b)
-            .sum()
-    }
-
-    #[test]
-    fn test_inner_prod_gadget() {
+    fn _test_inner_prod_pub_gadget(hiding: bool) {
         let rng = &mut test_rng();
 
-        let log_n = 16;
+        let log_n = 10;
         let n = 2usize.pow(log_n);
-        let domain = Domain::new(n, false);
+        let domain = Domain::new(n, hiding);
 
-        let a = random_vec(n, rng);
-        let b = random_vec(n, rng);
-        let a_col = domain.column(a.clone());
-        let b_col = domain.column(b.clone());
+        let a = random_vec(domain.capacity, rng);
+        let b = random_vec(domain.capacity, rng);
+        let ab = a.iter().zip(&b).map(|(a, b)| *a * b).sum();
+        let a = domain.public_column(a);
+        let b = domain.public_column(b);
 
-        let gadget = InnerProd::<Fq>::init(a_col, b_col, &domain);
-
-        assert_eq!(gadget.inner_prod, inner_prod(&a, &b));
-
-        let acc = &gadget.acc.evals.evals;
-        assert!(acc[0].is_zero());
-        for i in 0..n - 1 {
-            assert_eq!(acc[i + 1], acc[i] + a[i] * b[i])
-        }
+        let gadget = InnerProdPub::<Fq>::init(a, b, &domain);
+        assert_eq!(gadget.inner_prod, ab);
+        gadget.acc.assert_degree_at_most(n - 1);
+        assert!(gadget.acc.evals.evals[0].is_zero());
 
         let constraint_poly = gadget.constraints()[0].interpolate_by_ref();
+        assert_eq!(constraint_poly.degree(), 3 * n - 3);
 
-        assert_eq!(constraint_poly.degree(), 2 * n - 2);
+        domain.divide_by_vanishing_poly(&constraint_poly);
+    }
 
-        let quotient = domain.divide_by_vanishing_poly(&constraint_poly);
+    #[test]
+    fn test_inner_prod_pub_gadget() {
+        _test_inner_prod_pub_gadget(false);
+        _test_inner_prod_pub_gadget(true);
     }
-}
\ No newline at end of file
+}
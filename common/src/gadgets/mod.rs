@@ -4,11 +4,17 @@ use ark_poly::{Evaluations, GeneralEvaluationDomain};
 use ark_std::vec::Vec;
 
 pub mod booleanity;
-// pub mod inner_prod_pub;
 pub mod fixed_cells;
 pub mod inner_prod;
+pub mod inner_prod_pub;
 pub mod sw_cond_add;
 
+pub use booleanity::{BitColumn, Booleanity, BooleanityError, BooleanityValues};
+pub use fixed_cells::{FixedCells, FixedCellsValues};
+pub use inner_prod::{InnerProd, InnerProdValues};
+pub use inner_prod_pub::{InnerProdPub, InnerProdPubValues};
+pub use sw_cond_add::{AffineColumn, CondAdd, CondAddValues};
+
 pub trait ProverGadget<F: FftField> {
     // Columns populated by the gadget.
     fn witness_columns(&self) -> Vec<DensePolynomial<F>>;
@@ -16,6 +22,20 @@ pub trait ProverGadget<F: FftField> {
     // Constraint polynomials.
     fn constraints(&self) -> Vec<Evaluations<F>>;
 
+    // Names of the constraints, in the order of `constraints`, to tell which one is violated when debugging.
+    // By default, the constraints are numbered, that requires computing them.
+    fn constraint_labels(&self) -> &'static [&'static str] {
+        generic_constraint_labels(self.constraints().len())
+    }
+
+    fn constraint_polys_with_labels(&self) -> Vec<(&'static str, Evaluations<F>)> {
+        self.constraint_labels()
+            .iter()
+            .copied()
+            .zip(self.constraints())
+            .collect()
+    }
+
     // 'Linearized' parts of the constraint polynomials.
     fn constraints_linearized(&self, zeta: &F) -> Vec<DensePolynomial<F>>;
 
@@ -23,6 +43,31 @@ pub trait ProverGadget<F: FftField> {
     fn domain(&self) -> GeneralEvaluationDomain<F>;
 }
 
+// Labels `"c0", "c1", ...` for the first `n` constraints, for the gadgets and PIOPs that don't name theirs.
+pub(crate) fn generic_constraint_labels(n: usize) -> &'static [&'static str] {
+    const LABELS: [&str; 32] = [
+        "c0", "c1", "c2", "c3", "c4", "c5", "c6", "c7", "c8", "c9", "c10", "c11", "c12", "c13",
+        "c14", "c15", "c16", "c17", "c18", "c19", "c20", "c21", "c22", "c23", "c24", "c25", "c26",
+        "c27", "c28", "c29", "c30", "c31",
+    ];
+    assert!(n <= LABELS.len(), "too many constraints to label: {n}");
+    &LABELS[..n]
+}
+
 pub trait VerifierGadget<F: Field> {
     fn evaluate_constraints_main(&self) -> Vec<F>;
 }
+
+// Coefficients of the accumulator columns in the linearized constraints of a conditional addition gadget.
+// For each of the 2 constraints returns the coefficients `(c_acc_x, c_acc_y)`,
+// such that the linearized constraint is `c_acc_x * acc_x(X) + c_acc_y * acc_y(X)`.
+pub trait CondAddCoeffs<F: Field>: VerifierGadget<F> {
+    fn acc_coeffs_1(&self) -> (F, F);
+
+    fn acc_coeffs_2(&self) -> (F, F);
+}
+
+// A gadget that can both populate and evaluate its constraints.
+pub trait Gadget<F: FftField>: ProverGadget<F> + VerifierGadget<F> {}
+
+impl<F: FftField, T: ProverGadget<F> + VerifierGadget<F>> Gadget<F> for T {}
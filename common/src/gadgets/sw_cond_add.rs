@@ -1,14 +1,16 @@
 use ark_ec::short_weierstrass::{Affine, SWCurveConfig};
 use ark_ec::{AffineRepr, CurveGroup};
-use ark_ff::{FftField, Field};
+use ark_ff::{BigInteger, FftField, Field, PrimeField};
 use ark_poly::univariate::DensePolynomial;
 use ark_poly::{Evaluations, GeneralEvaluationDomain};
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::rand::Rng;
 use ark_std::{vec, vec::Vec};
 
 use crate::domain::Domain;
 use crate::gadgets::booleanity::BitColumn;
-use crate::gadgets::{ProverGadget, VerifierGadget};
+use crate::gadgets::{CondAddCoeffs, ProverGadget, VerifierGadget};
+use crate::seed::SeedSource;
 use crate::{const_evals, Column, FieldColumn};
 
 // A vec of affine points from the prime-order subgroup of the curve whose base field enables FFTs,
@@ -23,9 +25,9 @@ pub struct AffineColumn<F: FftField, P: AffineRepr<BaseField = F>> {
 impl<F: FftField, P: AffineRepr<BaseField = F>> AffineColumn<F, P> {
     fn column(points: Vec<P>, domain: &Domain<F>, hidden: bool) -> Self {
         assert!(points.iter().all(|p| !p.is_zero()));
-        let (xs, ys) = points.iter().map(|p| p.xy().unwrap()).unzip();
-        let xs = domain.column(xs, hidden);
-        let ys = domain.column(ys, hidden);
+        let coords = points.iter().map(|p| p.xy().unwrap()).unzip();
+        let rng = &mut getrandom_or_panic::getrandom_or_panic();
+        let (xs, ys) = domain.column_pair_with_rng(coords, hidden, rng);
         Self { points, xs, ys }
     }
     pub fn private_column(points: Vec<P>, domain: &Domain<F>) -> Self {
@@ -36,21 +38,69 @@ impl<F: FftField, P: AffineRepr<BaseField = F>> AffineColumn<F, P> {
         Self::column(points, domain, false)
     }
 
+    // Public column of `n` points, the `i`-th point being `f(i)`.
+    pub fn from_fn_public(n: usize, f: impl Fn(usize) -> P, domain: &Domain<F>) -> Self {
+        Self::public_column((0..n).map(f).collect(), domain)
+    }
+
+    pub fn private_column_with_seed_source(
+        points: Vec<P>,
+        domain: &Domain<F>,
+        seed_source: &dyn SeedSource,
+    ) -> Self {
+        Self::private_column_with_rng(points, domain, &mut seed_source.to_rng())
+    }
+
+    pub fn private_column_with_rng<R: Rng>(
+        points: Vec<P>,
+        domain: &Domain<F>,
+        rng: &mut R,
+    ) -> Self {
+        assert!(points.iter().all(|p| !p.is_zero()));
+        let coords = points.iter().map(|p| p.xy().unwrap()).unzip();
+        let (xs, ys) = domain.column_pair_with_rng(coords, true, rng);
+        Self { points, xs, ys }
+    }
+
     pub fn evaluate(&self, z: &F) -> (F, F) {
         (self.xs.evaluate(z), self.ys.evaluate(z))
     }
 }
 
+impl<F: PrimeField, P: AffineRepr<BaseField = F>> AffineColumn<F, P> {
+    // Columns of the little-endian bits of the x-coordinates: the `i`-th column consists of the `i`-th bits.
+    pub fn bit_decompose_x(&self, domain: &Domain<F>) -> Vec<BitColumn<F>> {
+        let bitlen = F::MODULUS_BIT_SIZE as usize;
+        let xs_bits: Vec<Vec<bool>> = self
+            .points
+            .iter()
+            .map(|p| p.x().unwrap().into_bigint().to_bits_le())
+            .collect();
+        (0..bitlen)
+            .map(|i| {
+                let bits = xs_bits.iter().map(|x_bits| x_bits[i]).collect();
+                BitColumn::init(bits, domain)
+            })
+            .collect()
+    }
+}
+
 // Conditional affine addition:
 // if the bit is set for a point, add the point to the acc and store,
-// otherwise copy the acc value
+// otherwise copy the acc value.
+// Generic over the point type, with the constraints implemented for short Weierstrass curves.
+// The acc starts from a seed point, that must be chosen so that the incomplete addition formula
+// never hits an exceptional case, see `CondAdd::init`.
 pub struct CondAdd<F: FftField, P: AffineRepr<BaseField = F>> {
+    // Selects the points to add
     bitmask: BitColumn<F>,
+    // The points to conditionally add
     points: AffineColumn<F, P>,
     // The polynomial `X - w^{n-1}` in the Lagrange basis
     not_last: FieldColumn<F>,
     // Accumulates the (conditional) rolling sum of the points
     pub acc: AffineColumn<F, P>,
+    // The sum of the selected points, i.e. the last value of the acc minus the seed
     pub result: P,
 }
 
@@ -70,20 +120,64 @@ where
     // As the SW addition formula used is not complete, the seed must be selected in a way that would prevent
     // exceptional cases (doublings or adding the opposite point).
     // The last point of the input column is ignored, as adding it would made the acc column overflow due the initial point.
+    // The bitmask may be shorter than the points column, then it is padded with 0s, meaning the remaining points are not added.
+    // The bit in the last row of the domain is never constrained.
     pub fn init(
         bitmask: BitColumn<F>,
         points: AffineColumn<F, Affine<Curve>>,
         seed: Affine<Curve>,
         domain: &Domain<F>,
     ) -> Self {
-        assert_eq!(bitmask.bits.len(), domain.capacity - 1);
+        Self::init_with_acc_column(bitmask, points, seed, domain, AffineColumn::private_column)
+    }
+
+    // Same as `init`, but the acc column is blinded with the randomness derived from the `seed_source`.
+    pub fn init_with_seed_source(
+        bitmask: BitColumn<F>,
+        points: AffineColumn<F, Affine<Curve>>,
+        seed: Affine<Curve>,
+        domain: &Domain<F>,
+        seed_source: &dyn SeedSource,
+    ) -> Self {
+        Self::init_with_acc_column(bitmask, points, seed, domain, |acc, domain| {
+            AffineColumn::private_column_with_seed_source(acc, domain, seed_source)
+        })
+    }
+
+    // Same as `init`, but the acc column is blinded with the randomness supplied by the caller.
+    pub fn init_with_rng<R: Rng>(
+        bitmask: BitColumn<F>,
+        points: AffineColumn<F, Affine<Curve>>,
+        seed: Affine<Curve>,
+        domain: &Domain<F>,
+        rng: &mut R,
+    ) -> Self {
+        Self::init_with_acc_column(bitmask, points, seed, domain, |acc, domain| {
+            AffineColumn::private_column_with_rng(acc, domain, rng)
+        })
+    }
+
+    fn init_with_acc_column(
+        bitmask: BitColumn<F>,
+        points: AffineColumn<F, Affine<Curve>>,
+        seed: Affine<Curve>,
+        domain: &Domain<F>,
+        acc_column: impl FnOnce(Vec<Affine<Curve>>, &Domain<F>) -> AffineColumn<F, Affine<Curve>>,
+    ) -> Self {
+        assert!(bitmask.len() < domain.capacity);
         assert_eq!(points.points.len(), domain.capacity - 1);
+        // Adding the seed to itself would hit the exceptional case of the incomplete addition formula.
+        // It's up to the caller to rule this out, e.g. with proofs of possession of the keys.
+        debug_assert!(
+            !points.points.contains(&seed),
+            "seed appears in points column"
+        );
         let not_last = domain.not_last_row.clone();
         let acc = bitmask
-            .bits
             .iter()
+            .chain(ark_std::iter::repeat(false))
             .zip(points.points.iter())
-            .scan(seed, |acc, (&b, point)| {
+            .scan(seed, |acc, (b, point)| {
                 if b {
                     *acc = (*acc + point).into_affine();
                 }
@@ -93,7 +187,7 @@ where
         let init_plus_result = acc.last().unwrap();
         let result = init_plus_result.into_group() - seed.into_group();
         let result = result.into_affine();
-        let acc = AffineColumn::private_column(acc, domain);
+        let acc = acc_column(acc, domain);
 
         Self {
             bitmask,
@@ -177,6 +271,10 @@ where
         vec![c1, c2]
     }
 
+    fn constraint_labels(&self) -> &'static [&'static str] {
+        &["sw_cond_add_c1", "sw_cond_add_c2"]
+    }
+
     fn constraints_linearized(&self, z: &F) -> Vec<DensePolynomial<F>> {
         let vals = self.evaluate_assignment(z);
         let acc_x = self.acc.xs.as_poly();
@@ -224,8 +322,8 @@ impl<F: Field> VerifierGadget<F> for CondAddValues<F> {
     }
 }
 
-impl<F: Field> CondAddValues<F> {
-    pub fn acc_coeffs_1(&self) -> (F, F) {
+impl<F: Field> CondAddCoeffs<F> for CondAddValues<F> {
+    fn acc_coeffs_1(&self) -> (F, F) {
         let b = self.bitmask;
         let (x1, _y1) = self.acc;
         let (x2, _y2) = self.points;
@@ -239,7 +337,7 @@ impl<F: Field> CondAddValues<F> {
         (c_acc_x, c_acc_y)
     }
 
-    pub fn acc_coeffs_2(&self) -> (F, F) {
+    fn acc_coeffs_2(&self) -> (F, F) {
         let b = self.bitmask;
         let (x1, y1) = self.acc;
         let (x2, y2) = self.points;
@@ -283,6 +381,13 @@ mod tests {
         let res = gadget.acc.points.last().unwrap();
         assert_eq!(res, &expected_res);
 
+        let labels: Vec<_> = gadget
+            .constraint_polys_with_labels()
+            .into_iter()
+            .map(|(label, _)| label)
+            .collect();
+        assert_eq!(labels, ["sw_cond_add_c1", "sw_cond_add_c2"]);
+
         let cs = gadget.constraints();
         let (c1, c2) = (&cs[0], &cs[1]);
         let c1 = c1.interpolate_by_ref();
@@ -296,9 +401,108 @@ mod tests {
         // test_gadget(gadget);
     }
 
+    #[test]
+    fn test_sw_cond_add_short_bitmask() {
+        let rng = &mut test_rng();
+        let domain = Domain::new(1024, true);
+        let seed = SWAffine::generator();
+
+        let bitmask = random_bitvec(domain.capacity / 2, 0.5, rng);
+        let points = random_vec::<SWAffine, _>(domain.capacity - 1, rng);
+        let expected_res = seed + cond_sum(&bitmask, &points[..bitmask.len()]);
+
+        let bitmask_col = BitColumn::init(bitmask, &domain);
+        let points_col = AffineColumn::private_column(points, &domain);
+        let gadget = CondAdd::init(bitmask_col, points_col, seed, &domain);
+        assert_eq!(gadget.acc.points.last().unwrap(), &expected_res);
+
+        for c in gadget.constraints() {
+            domain.divide_by_vanishing_poly(&c.interpolate());
+        }
+    }
+
+    #[test]
+    fn test_bit_decompose_x() {
+        let rng = &mut test_rng();
+        let domain = Domain::new(1024, false);
+        let points = random_vec::<SWAffine, _>(10, rng);
+        let col = AffineColumn::public_column(points.clone(), &domain);
+        let bit_cols = col.bit_decompose_x(&domain);
+        for (j, p) in points.iter().enumerate() {
+            let x_bits: Vec<bool> = bit_cols.iter().map(|col| col.bits[j]).collect();
+            let x =
+                <SWAffine as AffineRepr>::BaseField::from_bigint(BigInteger::from_bits_le(&x_bits));
+            assert_eq!(x, p.x());
+        }
+    }
+
     #[test]
     fn test_sw_cond_add_gadget() {
         _test_sw_cond_add_gadget(false);
         _test_sw_cond_add_gadget(true);
     }
 }
+
+#[cfg(test)]
+mod proptests {
+    use ark_ed_on_bls12_381_bandersnatch::{Fr, SWAffine};
+    use ark_std::Zero;
+    use proptest::prelude::*;
+
+    use super::*;
+
+    const DOMAIN_SIZE: usize = 16;
+
+    // Checks that the constraints vanish on the domain by evaluating them at the rows of the 4x domain.
+    fn constraints_hold(gadget: &CondAdd<<SWAffine as AffineRepr>::BaseField, SWAffine>) -> bool {
+        gadget
+            .constraints()
+            .iter()
+            .all(|c| c.evals.iter().step_by(4).all(|e| e.is_zero()))
+    }
+
+    fn gadget(
+        bitmask: Vec<bool>,
+        scalars: &[[u8; 32]],
+        domain: &Domain<<SWAffine as AffineRepr>::BaseField>,
+    ) -> CondAdd<<SWAffine as AffineRepr>::BaseField, SWAffine> {
+        let points = scalars
+            .iter()
+            .map(|s| (SWAffine::generator() * Fr::from_le_bytes_mod_order(s)).into_affine())
+            .collect();
+        let bitmask = BitColumn::init(bitmask, domain);
+        let points = AffineColumn::public_column(points, domain);
+        CondAdd::init(bitmask, points, SWAffine::generator(), domain)
+    }
+
+    fn witness() -> impl Strategy<Value = (Vec<bool>, Vec<[u8; 32]>, usize)> {
+        let n = DOMAIN_SIZE - 1;
+        (
+            proptest::collection::vec(proptest::bool::ANY, n),
+            proptest::collection::vec(prop::array::uniform32(any::<u8>()), n),
+            0..n,
+        )
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(32))]
+
+        #[test]
+        fn cond_add_is_complete((bitmask, scalars, _) in witness()) {
+            let domain = Domain::new(DOMAIN_SIZE, false);
+            let gadget = gadget(bitmask, &scalars, &domain);
+            prop_assert!(constraints_hold(&gadget));
+        }
+
+        #[test]
+        fn cond_add_is_sound((bitmask, scalars, i) in witness()) {
+            let domain = Domain::new(DOMAIN_SIZE, false);
+            let mut gadget = gadget(bitmask.clone(), &scalars, &domain);
+            // The accumulator was computed for the original bitmask.
+            let mut perturbed = bitmask;
+            perturbed[i] = !perturbed[i];
+            gadget.bitmask = BitColumn::init(perturbed, &domain);
+            prop_assert!(!constraints_hold(&gadget));
+        }
+    }
+}
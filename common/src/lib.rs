@@ -1,16 +1,23 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
+use ark_ec::hashing::curve_maps::elligator2::{Elligator2Config, Elligator2Map};
+use ark_ec::hashing::map_to_curve_hasher::MapToCurveBasedHasher;
+use ark_ec::hashing::HashToCurve;
+use ark_ec::twisted_edwards::{Affine, Projective};
+use ark_ff::field_hashers::DefaultFieldHasher;
 use ark_ff::{FftField, PrimeField};
 use ark_poly::univariate::DensePolynomial;
 use ark_poly::{EvaluationDomain, Evaluations, GeneralEvaluationDomain, Polynomial};
-use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, SerializationError};
 use ark_std::{vec, vec::Vec};
 use fflonk::pcs::{Commitment, PCS};
+use sha2::Sha512;
 
 pub mod domain;
 pub mod gadgets;
 pub mod piop;
 pub mod prover;
+pub mod seed;
 pub mod test_helpers;
 pub mod transcript;
 pub mod verifier;
@@ -43,9 +50,41 @@ impl<F: FftField> FieldColumn<F> {
         Evaluations::from_vec_and_domain(evals_4x, self.domain_4x())
     }
 
+    #[deprecated(note = "use `FieldColumn::constrained_evals`")]
     pub fn vals(&self) -> &[F] {
+        self.constrained_evals()
+    }
+
+    // Evaluations over the constrained part of the domain, i.e. the input column values.
+    pub fn constrained_evals(&self) -> &[F] {
         &self.evals.evals[..self.len]
     }
+
+    // Evaluations over the whole domain, consuming the column.
+    pub fn into_domain_evals(self) -> Vec<F> {
+        self.evals.evals
+    }
+
+    // Evaluations over the whole domain, including the padding and the blinding rows, unlike `constrained_evals`.
+    pub fn evaluate_at_domain(&self) -> &[F] {
+        &self.evals.evals
+    }
+
+    pub fn evaluate_batch(&self, points: &[F]) -> Vec<F> {
+        points.iter().map(|z| self.evaluate(z)).collect()
+    }
+
+    pub fn polynomial_degree(&self) -> usize {
+        self.poly.degree()
+    }
+
+    pub fn assert_degree_at_most(&self, max_degree: usize) {
+        let degree = self.polynomial_degree();
+        assert!(
+            degree <= max_degree,
+            "column degree {degree} exceeds {max_degree}"
+        );
+    }
 }
 
 impl<F: FftField> Column<F> for FieldColumn<F> {
@@ -66,13 +105,34 @@ pub fn const_evals<F: FftField>(c: F, domain: GeneralEvaluationDomain<F>) -> Eva
     Evaluations::from_vec_and_domain(vec![c; domain.size()], domain)
 }
 
+// Hashes the message to a point of a twisted Edwards curve
+// with `expand_message_xmd` over SHA-512 for hashing to the base field, and the arkworks Elligator2 map.
+// `dst` is the domain separation tag.
+// It isn't checked against the RFC 9380 test vectors, so the points may differ from other implementations.
+pub fn hash_to_curve_elligator2<Curve: Elligator2Config>(
+    message: &[u8],
+    dst: &[u8],
+) -> Affine<Curve> {
+    type Hasher<Curve> =
+        MapToCurveBasedHasher<Projective<Curve>, DefaultFieldHasher<Sha512>, Elligator2Map<Curve>>;
+    Hasher::<Curve>::new(dst)
+        .and_then(|hasher| hasher.hash(message))
+        .expect("Elligator2 map is defined for the curve")
+}
+
 pub trait ColumnsEvaluated<F: PrimeField>: CanonicalSerialize + CanonicalDeserialize {
+    // Length of the vec returned by `to_vec`.
+    const N: usize;
+
     fn to_vec(self) -> Vec<F>;
 }
 
 pub trait ColumnsCommited<F: PrimeField, C: Commitment<F>>:
     CanonicalSerialize + CanonicalDeserialize
 {
+    // Length of the vec returned by `to_vec`.
+    const N: usize;
+
     fn to_vec(self) -> Vec<C>;
 }
 
@@ -91,3 +151,67 @@ where
     pub agg_at_zeta_proof: CS::Proof,
     pub lin_at_zeta_omega_proof: CS::Proof,
 }
+
+impl<F, CS, Commitments, Evaluations> Proof<F, CS, Commitments, Evaluations>
+where
+    F: PrimeField,
+    CS: PCS<F>,
+    Commitments: ColumnsCommited<F, CS::C>,
+    Evaluations: ColumnsEvaluated<F>,
+{
+    // A "null" proof, used as a placeholder, has the quotient committed as the identity element.
+    // A real proof can't have that, as the quotient is randomized by the blinding.
+    pub fn is_trivial(&self) -> bool {
+        self.quotient_commitment == self.quotient_commitment.mul(F::zero())
+    }
+
+    pub fn to_compressed_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.compressed_size());
+        self.serialize_compressed(&mut bytes)
+            .expect("serialization into a vec doesn't fail");
+        bytes
+    }
+
+    pub fn from_compressed_bytes(bytes: &[u8]) -> Result<Self, SerializationError> {
+        Self::deserialize_compressed(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_ed_on_bls12_381_bandersnatch::{BandersnatchConfig, EdwardsAffine, Fq};
+    use ark_std::test_rng;
+
+    use crate::domain::Domain;
+    use crate::test_helpers::random_vec;
+
+    use super::*;
+
+    #[test]
+    fn test_evaluate_batch() {
+        let rng = &mut test_rng();
+        let domain = Domain::<Fq>::new(256, true);
+        let col = domain.private_column(random_vec(domain.capacity, rng));
+        let points: Vec<Fq> = random_vec(5, rng);
+        let evals = col.evaluate_batch(&points);
+        assert_eq!(evals.len(), points.len());
+        for (z, eval) in points.iter().zip(evals) {
+            assert_eq!(eval, col.evaluate(z));
+        }
+        assert!(col.evaluate_batch(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_hash_to_curve_elligator2() {
+        let dst = b"/w3f/ring-proof/test";
+        let p: EdwardsAffine = hash_to_curve_elligator2::<BandersnatchConfig>(b"message", dst);
+        assert!(p.is_on_curve());
+        assert!(p.is_in_correct_subgroup_assuming_on_curve());
+        assert_eq!(p, hash_to_curve_elligator2(b"message", dst));
+        assert_ne!(
+            p,
+            hash_to_curve_elligator2(b"message", b"/w3f/ring-proof/other")
+        );
+        assert_ne!(p, hash_to_curve_elligator2(b"other", dst));
+    }
+}
@@ -1,11 +1,12 @@
 use ark_ff::PrimeField;
 use ark_poly::univariate::DensePolynomial;
-use ark_poly::Evaluations;
+use ark_poly::{EvaluationDomain, Evaluations};
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
-use ark_std::vec::Vec;
+use ark_std::{vec, vec::Vec};
 use fflonk::pcs::Commitment;
 
-use crate::domain::{Domain, EvaluatedDomain};
+use crate::domain::{Domain, EvaluatedDomain, ZK_ROWS};
+use crate::gadgets::generic_constraint_labels;
 use crate::{ColumnsCommited, ColumnsEvaluated};
 
 pub trait ProverPiop<F: PrimeField, C: Commitment<F>> {
@@ -29,6 +30,18 @@ pub trait ProverPiop<F: PrimeField, C: Commitment<F>> {
     // Constraint polynomials in evaluation form.
     fn constraints(&self) -> Vec<Evaluations<F>>;
 
+    // Names of the constraints, in the order of `constraints`, to tell which one is violated when debugging.
+    // By default, the constraints are numbered, that requires computing them.
+    fn constraint_labels(&self) -> Vec<&'static str> {
+        generic_constraint_labels(self.constraints().len()).to_vec()
+    }
+
+    // Linear combination of the constraint polynomials with the coefficients `alphas`, in evaluation form,
+    // so that it can be interpolated with a single IFFT.
+    fn constraints_aggregated(&self, alphas: &[F]) -> Evaluations<F> {
+        aggregate_evaluations(&self.constraints(), alphas)
+    }
+
     // 'Linearized' parts of constraint polynomials.
     // For a constraint of the form C = C(c1(X),...,ck(X),c1(wX),...,ck(wX)), where ci's are of degree n,
     // and an evaluation point z, it is a degree n polynomial r = C(c1(z),...,ck(z),c1(X),...,ck(X)).
@@ -39,6 +52,43 @@ pub trait ProverPiop<F: PrimeField, C: Commitment<F>> {
 
     // The result of the computation.
     fn result(&self) -> Self::Instance;
+
+    // All the public inputs of the computation, in the order they are added to the transcript.
+    // By default, the result is the only public input.
+    fn public_inputs(&self) -> Vec<impl CanonicalSerialize> {
+        vec![self.result()]
+    }
+}
+
+pub(crate) fn aggregate_evaluations<F: PrimeField>(
+    polys: &[Evaluations<F>],
+    coeffs: &[F],
+) -> Evaluations<F> {
+    assert_eq!(coeffs.len(), polys.len());
+    polys
+        .iter()
+        .zip(coeffs.iter())
+        .map(|(p, &c)| p * c)
+        .reduce(|acc, p| &acc + &p)
+        .unwrap()
+}
+
+// Checked proving mode: panics, naming the first constraint that doesn't vanish on the domain,
+// except the zero-knowledge rows, as the proof would be rejected anyway.
+pub(crate) fn assert_constraints_hold<F: PrimeField>(
+    domain: &Domain<F>,
+    labels: &[&str],
+    constraints: &[Evaluations<F>],
+) {
+    assert_eq!(labels.len(), constraints.len());
+    let n = domain.domain().size();
+    let rows = if domain.hiding { n - ZK_ROWS } else { n };
+    for (i, (label, c)) in labels.iter().zip(constraints).enumerate() {
+        let step = c.evals.len() / n;
+        if let Some(row) = (0..rows).find(|row| !c.evals[row * step].is_zero()) {
+            panic!("constraint #{i} `{label}` doesn't hold in row {row}");
+        }
+    }
 }
 
 pub trait VerifierPiop<F: PrimeField, C: Commitment<F>> {
@@ -49,7 +99,38 @@ pub trait VerifierPiop<F: PrimeField, C: Commitment<F>> {
 
     fn evaluate_constraints_main(&self) -> Vec<F>;
 
-    fn constraint_polynomials_linearized_commitments(&self) -> Vec<C>;
+    // Commitment to the linear combination of the linearized constraint polynomials,
+    // `agg_coeffs` are the coefficients for all the constraints, in the order of `evaluate_constraints_main`.
+    fn linearized_constraint_commitment(&self, agg_coeffs: &[F]) -> C;
 
     fn domain_evaluated(&self) -> &EvaluatedDomain<F>;
+
+    // Size of the domain over which the columns are defined.
+    fn domain_size(&self) -> usize {
+        self.domain_evaluated().domain.size()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_ed_on_bls12_381_bandersnatch::Fq;
+    use ark_ff::{One, Zero};
+
+    use crate::gadgets::{BitColumn, Booleanity};
+
+    use super::*;
+
+    #[test]
+    #[should_panic(expected = "constraint #0 `booleanity` doesn't hold in row 1")]
+    fn test_assert_constraints_hold() {
+        let domain = Domain::new(16, true);
+        let col = domain.public_column(vec![Fq::one(), Fq::from(2u8), Fq::zero()]);
+        let bits = vec![true, true, false];
+        let booleanity = Booleanity::init(BitColumn { bits, col });
+        assert_constraints_hold(
+            &domain,
+            booleanity.constraint_labels(),
+            &booleanity.constraints(),
+        );
+    }
 }
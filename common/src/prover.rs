@@ -5,7 +5,7 @@ use ark_std::vec;
 use fflonk::aggregation::single::aggregate_polys;
 use fflonk::pcs::PCS;
 
-use crate::piop::ProverPiop;
+use crate::piop::{aggregate_evaluations, assert_constraints_hold, ProverPiop};
 use crate::transcript::PlonkTranscript;
 use crate::Proof;
 
@@ -20,11 +20,11 @@ pub struct PlonkProver<F: PrimeField, CS: PCS<F>, T: PlonkTranscript<F, CS>> {
 impl<F: PrimeField, CS: PCS<F>, T: PlonkTranscript<F, CS>> PlonkProver<F, CS, T> {
     pub fn init(
         pcs_ck: CS::CK,
-        verifier_key: impl CanonicalSerialize, //TODO: a type,
+        verifier_key: &impl CanonicalSerialize, //TODO: a type,
         empty_transcript: T,
     ) -> Self {
         let mut transcript_prelude = empty_transcript;
-        transcript_prelude._add_serializable(b"vk", &verifier_key);
+        transcript_prelude._add_serializable(b"vk", verifier_key);
 
         Self {
             pcs_ck,
@@ -35,19 +35,47 @@ impl<F: PrimeField, CS: PCS<F>, T: PlonkTranscript<F, CS>> PlonkProver<F, CS, T>
     pub fn prove<P>(&self, piop: P) -> Proof<F, CS, P::Commitments, P::Evaluations>
     where
         P: ProverPiop<F, CS::C>,
+    {
+        self.prove_with(piop, |piop, alphas| piop.constraints_aggregated(alphas))
+    }
+
+    // Same as `prove`, but panics naming the first violated constraint, instead of producing an invalid proof.
+    // Meant for debugging a witness.
+    pub fn prove_checked<P>(&self, piop: P) -> Proof<F, CS, P::Commitments, P::Evaluations>
+    where
+        P: ProverPiop<F, CS::C>,
+    {
+        self.prove_with(piop, |piop, alphas| {
+            let constraints = piop.constraints();
+            assert_constraints_hold(piop.domain(), &piop.constraint_labels(), &constraints);
+            aggregate_evaluations(&constraints, alphas)
+        })
+    }
+
+    // `aggregate_constraints` returns the linear combination of the constraints with the given coefficients.
+    fn prove_with<P, Fun>(
+        &self,
+        piop: P,
+        aggregate_constraints: Fun,
+    ) -> Proof<F, CS, P::Commitments, P::Evaluations>
+    where
+        P: ProverPiop<F, CS::C>,
+        Fun: FnOnce(&P, &[F]) -> Evaluations<F>,
     {
         let mut transcript = self.transcript_prelude.clone();
-        transcript.add_instance(&piop.result());
+        for public_input in piop.public_inputs() {
+            transcript.add_instance(&public_input);
+        }
         // ROUND 1
         // The prover commits to the columns.
         let column_commitments = piop.committed_columns(|p| CS::commit(&self.pcs_ck, p));
         transcript.add_committed_cols(&column_commitments);
 
         // ROUND 2
-        let constraint_polys = piop.constraints();
-        let alphas = transcript.get_constraints_aggregation_coeffs(constraint_polys.len());
+        // One coefficient per constraint, as there is one label per constraint.
+        let alphas = transcript.get_constraints_aggregation_coeffs(piop.constraint_labels().len());
         // Aggregate constraint polynomials in evaluation form...
-        let agg_constraint_poly = Self::aggregate_evaluations(&constraint_polys, &alphas);
+        let agg_constraint_poly = aggregate_constraints(&piop, &alphas);
         // ...and then interpolate (to save some FFTs).
         let agg_constraint_poly = agg_constraint_poly.interpolate();
         let quotient_poly = piop.domain().divide_by_vanishing_poly(&agg_constraint_poly);
@@ -82,14 +110,4 @@ impl<F: PrimeField, CS: PCS<F>, T: PlonkTranscript<F, CS>> PlonkProver<F, CS, T>
             lin_at_zeta_omega_proof,
         }
     }
-
-    fn aggregate_evaluations(polys: &[Evaluations<F>], coeffs: &[F]) -> Evaluations<F> {
-        assert_eq!(coeffs.len(), polys.len());
-        polys
-            .iter()
-            .zip(coeffs.iter())
-            .map(|(p, &c)| p * c)
-            .reduce(|acc, p| &acc + &p)
-            .unwrap()
-    }
 }
@@ -0,0 +1,38 @@
+use ark_std::rand::rngs::StdRng;
+use ark_std::rand::SeedableRng;
+use blake2::digest::consts::U32;
+use blake2::Digest;
+
+// Source of the 32-byte seed used to sample the blinding values of private columns.
+pub trait SeedSource {
+    fn get_seed(&self) -> [u8; 32];
+
+    fn to_rng(&self) -> StdRng {
+        StdRng::from_seed(self.get_seed())
+    }
+}
+
+// Seeds from the system randomness.
+#[cfg(feature = "std")]
+pub struct OsSeedSource;
+
+#[cfg(feature = "std")]
+impl SeedSource for OsSeedSource {
+    fn get_seed(&self) -> [u8; 32] {
+        use rand_core::RngCore;
+        let mut seed = [0u8; 32];
+        getrandom_or_panic::getrandom_or_panic().fill_bytes(&mut seed);
+        seed
+    }
+}
+
+// Derives the seed from the message with Blake2b, for environments without system randomness.
+// The same message always results in the same blinding, so the message should be unique per proof
+// and contain secret data, or the blinding provides no zero-knowledge.
+pub struct DeterministicSeedSource<'a>(pub &'a [u8]);
+
+impl SeedSource for DeterministicSeedSource<'_> {
+    fn get_seed(&self) -> [u8; 32] {
+        blake2::Blake2b::<U32>::digest(self.0).into()
+    }
+}
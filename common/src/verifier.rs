@@ -1,7 +1,7 @@
 use ark_ff::{Field, PrimeField};
 use ark_serialize::CanonicalSerialize;
 use ark_std::rand::Rng;
-use ark_std::{vec, vec::Vec};
+use ark_std::vec::Vec;
 use fflonk::pcs::{Commitment, PcsParams, PCS};
 use rand_core::RngCore;
 
@@ -39,6 +39,92 @@ impl<F: PrimeField, CS: PCS<F>, T: PlonkTranscript<F, CS>> PlonkVerifier<F, CS,
         challenges: Challenges<F>,
         rng: &mut R,
     ) -> bool
+    where
+        Piop: VerifierPiop<F, CS::C>,
+        Commitments: ColumnsCommited<F, CS::C>,
+        Evaluations: ColumnsEvaluated<F>,
+    {
+        self.verify_returning_challenges(piop, proof, challenges, rng)
+            .is_some()
+    }
+
+    // Returns the challenges used if the proof is valid, to be reused by the protocols composed with this one.
+    pub fn verify_returning_challenges<Piop, Commitments, Evaluations, R: Rng>(
+        &self,
+        piop: Piop,
+        proof: Proof<F, CS, Commitments, Evaluations>,
+        challenges: Challenges<F>,
+        rng: &mut R,
+    ) -> Option<Challenges<F>>
+    where
+        Piop: VerifierPiop<F, CS::C>,
+        Commitments: ColumnsCommited<F, CS::C>,
+        Evaluations: ColumnsEvaluated<F>,
+    {
+        if Self::in_domain(&piop, challenges.zeta) {
+            return None;
+        }
+        let openings = Self::openings(piop, proof, &challenges);
+        self.verify_openings(openings, rng).then_some(challenges)
+    }
+
+    // Verifies several proofs for the same verifier key at the cost of one,
+    // by checking all the openings together with the randomness from `rng`.
+    pub fn verify_batch<Piop, Commitments, Evaluations, R: Rng>(
+        &self,
+        instances: impl IntoIterator<
+            Item = (Piop, Proof<F, CS, Commitments, Evaluations>, Challenges<F>),
+        >,
+        rng: &mut R,
+    ) -> bool
+    where
+        Piop: VerifierPiop<F, CS::C>,
+        Commitments: ColumnsCommited<F, CS::C>,
+        Evaluations: ColumnsEvaluated<F>,
+    {
+        let instances: Vec<_> = instances.into_iter().collect();
+        if instances
+            .iter()
+            .any(|(piop, _, challenges)| Self::in_domain(piop, challenges.zeta))
+        {
+            return false;
+        }
+        let openings = instances
+            .into_iter()
+            .flat_map(|(piop, proof, challenges)| Self::openings(piop, proof, &challenges));
+        self.verify_openings(openings, rng)
+    }
+
+    // The vanishing polynomial of the domain is `0` in the domain points,
+    // so the quotient can't be evaluated there, and the constraints aren't checked.
+    fn in_domain<Piop: VerifierPiop<F, CS::C>>(piop: &Piop, zeta: F) -> bool {
+        zeta.pow([piop.domain_size() as u64]).is_one()
+    }
+
+    fn verify_openings<R: Rng>(
+        &self,
+        openings: impl IntoIterator<Item = Opening<F, CS>>,
+        rng: &mut R,
+    ) -> bool {
+        let (mut commitments, mut points, mut values, mut proofs) =
+            (Vec::new(), Vec::new(), Vec::new(), Vec::new());
+        for (c, x, y, proof) in openings {
+            commitments.push(c);
+            points.push(x);
+            values.push(y);
+            proofs.push(proof);
+        }
+        CS::batch_verify(&self.pcs_vk, commitments, points, values, proofs, rng)
+    }
+
+    // The claims `(commitment, point, value, proof)` that the proof is reduced to:
+    // the aggregated polynomial evaluates to the aggregated value in `zeta`,
+    // and the linearization polynomial evaluates to `lin_at_zeta_omega` in `zeta.omega`.
+    fn openings<Piop, Commitments, Evaluations>(
+        piop: Piop,
+        proof: Proof<F, CS, Commitments, Evaluations>,
+        challenges: &Challenges<F>,
+    ) -> [Opening<F, CS>; 2]
     where
         Piop: VerifierPiop<F, CS::C>,
         Commitments: ColumnsCommited<F, CS::C>,
@@ -73,24 +159,39 @@ impl<F: PrimeField, CS: PCS<F>, T: PlonkTranscript<F, CS>> PlonkVerifier<F, CS,
             .map(|(y, r)| y * r)
             .sum();
 
-        let lin_pices = piop.constraint_polynomials_linearized_commitments();
-        let lin_comm = CS::C::combine(&challenges.alphas[..3], &lin_pices);
+        let lin_comm = piop.linearized_constraint_commitment(&challenges.alphas);
 
         let zeta_omega = zeta * domain_evaluated.omega();
 
-        CS::batch_verify(
-            &self.pcs_vk,
-            vec![cl, lin_comm],
-            vec![challenges.zeta, zeta_omega],
-            vec![agg_y, proof.lin_at_zeta_omega],
-            vec![proof.agg_at_zeta_proof, proof.lin_at_zeta_omega_proof],
-            rng,
-        )
+        [
+            (cl, challenges.zeta, agg_y, proof.agg_at_zeta_proof),
+            (
+                lin_comm,
+                zeta_omega,
+                proof.lin_at_zeta_omega,
+                proof.lin_at_zeta_omega_proof,
+            ),
+        ]
     }
 
+    // `public_inputs` should be the same as returned by `ProverPiop::public_inputs`, in the same order.
+    // The challenges are a deterministic function of the transcript,
+    // and so can be used to generate test vectors for other implementations.
+    // The transcript is fed with the following (label, message) pairs, each message serialized with `CanonicalSerialize`:
+    // 1. ("vk", verifier_key) -- on `PlonkVerifier::init`,
+    // 2. ("instance", public_input) for each of the `public_inputs`,
+    // 3. ("committed_cols", proof.column_commitments),
+    //    `n_constraints` challenges `alphas` are squeezed with label "constraints_aggregation",
+    // 4. ("quotient", proof.quotient_commitment),
+    //    the challenge `zeta` is squeezed with label "evaluation_point",
+    // 5. ("register_evaluations", proof.columns_at_zeta),
+    // 6. ("shifted_linearization_evaluation", proof.lin_at_zeta_omega),
+    //    `n_polys` challenges `nus` are squeezed with label "kzg_aggregation",
+    // 7. ("kzg_proof_zeta", proof.agg_at_zeta_proof), ("kzg_proof_zeta_omega", proof.lin_at_zeta_omega_proof),
+    //    the returned rng is derived from the transcript with `PlonkTranscript::to_rng`.
     pub fn restore_challenges<Commitments, Evaluations>(
         &self,
-        instance: &impl CanonicalSerialize,
+        public_inputs: &[impl CanonicalSerialize],
         proof: &Proof<F, CS, Commitments, Evaluations>,
         n_polys: usize,
         n_constraints: usize,
@@ -100,7 +201,9 @@ impl<F: PrimeField, CS: PCS<F>, T: PlonkTranscript<F, CS>> PlonkVerifier<F, CS,
         Evaluations: ColumnsEvaluated<F>,
     {
         let mut transcript = self.transcript_prelude.clone();
-        transcript.add_instance(instance);
+        for public_input in public_inputs {
+            transcript.add_instance(public_input);
+        }
         transcript.add_committed_cols(&proof.column_commitments);
         // let r = transcript.get_bitmask_aggregation_challenge();
         // transcript.append_2nd_round_register_commitments(&proof.additional_commitments);
@@ -115,6 +218,8 @@ impl<F: PrimeField, CS: PCS<F>, T: PlonkTranscript<F, CS>> PlonkVerifier<F, CS,
     }
 }
 
+type Opening<F, CS> = (<CS as PCS<F>>::C, F, F, <CS as PCS<F>>::Proof);
+
 pub struct Challenges<F: Field> {
     pub alphas: Vec<F>,
     pub zeta: F,
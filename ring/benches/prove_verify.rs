@@ -0,0 +1,92 @@
+use ark_bls12_381::Bls12_381;
+use ark_ec::CurveGroup;
+use ark_ed_on_bls12_381_bandersnatch::{BandersnatchConfig, Fq, Fr, SWAffine};
+use ark_std::{test_rng, UniformRand};
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
+
+use common::test_helpers::random_vec;
+use ring::pcs::kzg::KZG;
+use ring::pcs::{IdentityCommitment, PCS};
+use ring::ring_prover::RingProver;
+use ring::ring_verifier::RingVerifier;
+use ring::{find_complement_point, index, ArkTranscript, Domain, PiopParams, RingProof};
+
+const DOMAIN_SIZES: [usize; 3] = [512, 1024, 4096];
+
+fn setup<CS: PCS<Fq>>(domain_size: usize) -> (CS::Params, PiopParams<Fq, BandersnatchConfig>) {
+    let rng = &mut test_rng();
+    let pcs_params = CS::setup(3 * domain_size, rng);
+    let domain = Domain::new(domain_size, true);
+    let h = SWAffine::rand(rng);
+    let seed = find_complement_point::<BandersnatchConfig>();
+    let piop_params = PiopParams::setup(domain, h, seed);
+    (pcs_params, piop_params)
+}
+
+fn bench_pcs<CS: PCS<Fq>>(c: &mut Criterion, pcs_name: &str) {
+    let rng = &mut test_rng();
+    let mut group = c.benchmark_group(format!("ring_{pcs_name}"));
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(1));
+
+    for domain_size in DOMAIN_SIZES {
+        let (pcs_params, piop_params) = setup::<CS>(domain_size);
+        let pks = random_vec::<SWAffine, _>(piop_params.keyset_part_size, rng);
+
+        group.bench_with_input(
+            BenchmarkId::new("index", domain_size),
+            &domain_size,
+            |b, _| b.iter(|| index::<_, CS, _>(&pcs_params, &piop_params, &pks)),
+        );
+
+        let (prover_key, verifier_key) = index::<_, CS, _>(&pcs_params, &piop_params, &pks);
+        let k = pks.len() / 2;
+        let ring_prover = RingProver::builder()
+            .with_prover_key(prover_key)
+            .with_piop_params(piop_params.clone())
+            .with_index(k)
+            .with_transcript(ArkTranscript::new(b"ring-proof-bench"))
+            .build()
+            .unwrap();
+        let ring_verifier = RingVerifier::builder()
+            .with_verifier_key(verifier_key)
+            .with_piop_params(piop_params.clone())
+            .with_transcript(ArkTranscript::new(b"ring-proof-bench"))
+            .build()
+            .unwrap();
+        let secret = Fr::rand(rng);
+        let result = (piop_params.scalar_multiple_of_h(secret) + pks[k]).into_affine();
+
+        group.bench_with_input(
+            BenchmarkId::new("prove", domain_size),
+            &domain_size,
+            |b, _| b.iter(|| ring_prover.prove(secret)),
+        );
+
+        // The verifier consumes the proof, that isn't `Clone` for a generic `CS`.
+        let proof = ring_prover.prove(secret).to_compressed_bytes();
+        group.bench_with_input(
+            BenchmarkId::new("verify", domain_size),
+            &domain_size,
+            |b, _| {
+                b.iter_batched(
+                    || RingProof::<Fq, CS>::from_compressed_bytes(&proof).unwrap(),
+                    |proof| ring_verifier.verify_ring_proof(proof, result),
+                    BatchSize::SmallInput,
+                )
+            },
+        );
+    }
+
+    group.finish();
+}
+
+// `IdentityCommitment` skips the commitments and the openings,
+// so the difference with KZG is the cost of the polynomial commitment scheme.
+fn bench_ring_proof(c: &mut Criterion) {
+    bench_pcs::<KZG<Bls12_381>>(c, "kzg");
+    bench_pcs::<IdentityCommitment>(c, "identity");
+}
+
+criterion_group!(benches, bench_ring_proof);
+criterion_main!(benches);
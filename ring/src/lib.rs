@@ -10,31 +10,56 @@ use ark_std::rand::RngCore;
 use fflonk::pcs::PCS;
 
 pub use common::domain::Domain;
-use common::Proof;
+use common::domain::ZK_ROWS;
+use common::{ColumnsCommited, ColumnsEvaluated, Proof};
 pub use piop::index;
 
-pub use crate::piop::{params::PiopParams, FixedColumnsCommitted, ProverKey, VerifierKey};
-use crate::piop::{RingCommitments, RingEvaluations};
+pub use crate::piop::{
+    params::PiopParams, FixedColumnsCommitted, ProverKey, RingCommitments, RingEvaluations,
+    RingOpenings, VerifierKey,
+};
 
 mod piop;
 pub mod ring;
 pub mod ring_prover;
 pub mod ring_verifier;
+pub mod vrf_combined;
 
 pub type RingProof<F, CS> = Proof<F, CS, RingCommitments<F, <CS as PCS<F>>::C>, RingEvaluations<F>>;
 
+// Size of a serialized `RingProof<F, CS>`, given the sizes of a serialized commitment, field element, and opening proof.
+// Doesn't depend on the domain size.
+pub const fn ring_proof_size_estimate<F: PrimeField, CS: PCS<F>>(
+    commitment_size: usize,
+    fr_size: usize,
+    opening_proof_size: usize,
+) -> usize {
+    // the column commitments and the quotient commitment
+    let n_commitments = <RingCommitments<F, CS::C> as ColumnsCommited<F, CS::C>>::N + 1;
+    // the column evaluations and the linearization polynomial evaluation
+    let n_evaluations = <RingEvaluations<F> as ColumnsEvaluated<F>>::N + 1;
+    n_commitments * commitment_size + n_evaluations * fr_size + 2 * opening_proof_size
+}
+
 /// Polynomial Commitment Schemes.
 pub use fflonk::pcs;
 
+// The smallest (hiding) domain size, such that the keyset part of the columns fits `keyset_size` keys.
+// The columns also hold the powers of `H`, one per scalar bit, and an unconstrained last row.
+pub fn minimal_domain_size_for_keyset<P: AffineRepr>(keyset_size: usize) -> usize {
+    let scalar_bitlen = P::ScalarField::MODULUS_BIT_SIZE as usize;
+    (keyset_size + scalar_bitlen + 1 + ZK_ROWS).next_power_of_two()
+}
+
 // Calling the method for a prime-order curve results in an infinite loop.
 pub fn find_complement_point<Curve: SWCurveConfig>() -> Affine<Curve> {
     let mut x = Curve::BaseField::zero();
     loop {
         let p = Affine::<Curve>::get_point_from_x_unchecked(x, false);
-        if p.is_some() && !p.unwrap().is_in_correct_subgroup_assuming_on_curve() {
-            return p.unwrap();
+        if let Some(p) = p.filter(|p| !p.is_in_correct_subgroup_assuming_on_curve()) {
+            return p;
         }
-        x = x + Curve::BaseField::one()
+        x += Curve::BaseField::one()
     }
 }
 
@@ -58,6 +83,37 @@ pub(crate) fn hash_to_curve<F: PrimeField, Curve: SWCurveConfig<BaseField = F>>(
     }
 }
 
+// A required parameter wasn't supplied to a builder, or is inconsistent with the others.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BuilderError {
+    MissingProverKey,
+    MissingVerifierKey,
+    MissingPiopParams,
+    MissingIndex,
+    MissingTranscript,
+    // The prover's index doesn't fit into the keyset part of the columns.
+    IndexOutOfRange,
+}
+
+// The reason a ring proof was rejected.
+// The constraints are checked together with the openings in a single batched KZG check,
+// so a violated constraint shows as `KzgVerificationFailed`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RingProofError {
+    // The verifier key was computed for a domain of another size than the one in the PIOP parameters.
+    DomainMismatch,
+    // The quotient commitment is the identity, that can't be in an honestly generated proof.
+    MalformedProof,
+    KzgVerificationFailed,
+}
+
+#[cfg(feature = "async")]
+#[derive(Debug)]
+pub enum ProverError {
+    // The blocking task running the prover panicked or was cancelled.
+    SpawnFailed(tokio::task::JoinError),
+}
+
 #[derive(Clone)]
 pub struct ArkTranscript(ark_transcript::Transcript);
 
@@ -92,6 +148,7 @@ mod tests {
     use ark_std::rand::Rng;
     use ark_std::{end_timer, start_timer, test_rng, UniformRand};
     use fflonk::pcs::kzg::KZG;
+    use fflonk::pcs::PcsParams;
 
     use common::test_helpers::random_vec;
 
@@ -102,7 +159,7 @@ mod tests {
 
     use super::*;
 
-    fn _test_ring_proof<CS: PCS<Fq>>(domain_size: usize) {
+    fn _test_ring_proof<CS: PCS<Fq>>(domain_size: usize) -> usize {
         let rng = &mut test_rng();
 
         let (pcs_params, piop_params) = setup::<_, CS>(rng, domain_size);
@@ -111,32 +168,227 @@ mod tests {
         let keyset_size: usize = rng.gen_range(0..max_keyset_size);
         let pks = random_vec::<SWAffine, _>(keyset_size, rng);
         let k = rng.gen_range(0..keyset_size); // prover's secret index
-        let pk = pks[k].clone();
+        let pk = pks[k];
 
         let (prover_key, verifier_key) = index::<_, CS, _>(&pcs_params, &piop_params, &pks);
 
         // PROOF generation
         let secret = Fr::rand(rng); // prover's secret scalar
         let result = piop_params.h.mul(secret) + pk;
-        let ring_prover = RingProver::init(
-            prover_key,
-            piop_params.clone(),
-            k,
-            ArkTranscript::new(b"ring-vrf-test"),
-        );
+        let ring_prover = RingProver::builder()
+            .with_prover_key(prover_key)
+            .with_piop_params(piop_params.clone())
+            .with_index(k)
+            .with_transcript(ArkTranscript::new(b"ring-vrf-test"))
+            .build()
+            .unwrap();
+        assert_eq!(ring_prover.key_index(), k);
         let t_prove = start_timer!(|| "Prove");
         let proof = ring_prover.prove(secret);
         end_timer!(t_prove);
-
-        let ring_verifier = RingVerifier::init(
-            verifier_key,
-            piop_params,
-            ArkTranscript::new(b"ring-vrf-test"),
-        );
+        assert!(!proof.is_trivial());
+        let proof_size = proof.compressed_size();
+
+        let ring_verifier = RingVerifier::builder()
+            .with_verifier_key(verifier_key)
+            .with_piop_params(piop_params)
+            .with_transcript(ArkTranscript::new(b"ring-vrf-test"))
+            .build()
+            .unwrap();
         let t_verify = start_timer!(|| "Verify");
         let res = ring_verifier.verify_ring_proof(proof, result.into_affine());
         end_timer!(t_verify);
         assert!(res);
+        proof_size
+    }
+
+    #[test]
+    fn test_prove_with_external_randomness() {
+        let rng = &mut test_rng();
+        let (pcs_params, piop_params) = setup::<_, KZG<Bls12_381>>(rng, 2usize.pow(9));
+        let pks = random_vec::<SWAffine, _>(10, rng);
+        let (prover_key, _) = index::<_, KZG<Bls12_381>, _>(&pcs_params, &piop_params, &pks);
+
+        let secret = Fr::rand(rng);
+        let result = piop_params.h.mul(secret) + pks[0];
+        let ring_prover = RingProver::builder()
+            .with_prover_key(prover_key)
+            .with_piop_params(piop_params.clone())
+            .with_index(0)
+            .with_transcript(ArkTranscript::new(b"ring-vrf-test"))
+            .build()
+            .unwrap();
+        let verifier_key = ring_prover.verifier_key().clone();
+        let proof1 = ring_prover.prove_with_external_randomness(secret, &mut test_rng());
+        let proof2 = ring_prover.prove_with_external_randomness(secret, &mut test_rng());
+        let bytes = proof1.to_compressed_bytes();
+        assert_eq!(bytes, proof2.to_compressed_bytes());
+        let proof1 = RingProof::<Fq, KZG<Bls12_381>>::from_compressed_bytes(&bytes).unwrap();
+
+        let ring_verifier = RingVerifier::builder()
+            .with_verifier_key(verifier_key)
+            .with_piop_params(piop_params)
+            .with_transcript(ArkTranscript::new(b"ring-vrf-test"))
+            .build()
+            .unwrap();
+        assert!(ring_verifier.verify_ring_proof(proof1, result.into_affine()));
+    }
+
+    #[test]
+    fn test_verify_returning_error() {
+        let rng = &mut test_rng();
+        let (pcs_params, piop_params) = setup::<_, KZG<Bls12_381>>(rng, 2usize.pow(9));
+        let pks = random_vec::<SWAffine, _>(10, rng);
+        let (prover_key, verifier_key) =
+            index::<_, KZG<Bls12_381>, _>(&pcs_params, &piop_params, &pks);
+
+        let secret = Fr::rand(rng);
+        let result = (piop_params.h.mul(secret) + pks[0]).into_affine();
+        let ring_prover = RingProver::builder()
+            .with_prover_key(prover_key)
+            .with_piop_params(piop_params.clone())
+            .with_index(0)
+            .with_transcript(ArkTranscript::new(b"ring-vrf-test"))
+            .build()
+            .unwrap();
+        let ring_verifier = RingVerifier::builder()
+            .with_verifier_key(verifier_key.clone())
+            .with_piop_params(piop_params)
+            .with_transcript(ArkTranscript::new(b"ring-vrf-test"))
+            .build()
+            .unwrap();
+        assert_eq!(
+            ring_verifier.verify_returning_error(ring_prover.prove(secret), result),
+            Ok(())
+        );
+        assert_eq!(
+            ring_verifier.verify_returning_error(ring_prover.prove(secret), pks[0]),
+            Err(RingProofError::KzgVerificationFailed)
+        );
+
+        let (_, other_piop_params) = setup::<_, KZG<Bls12_381>>(rng, 2usize.pow(10));
+        let ring_verifier = RingVerifier::builder()
+            .with_verifier_key(verifier_key)
+            .with_piop_params(other_piop_params)
+            .with_transcript(ArkTranscript::new(b"ring-vrf-test"))
+            .build()
+            .unwrap();
+        assert_eq!(
+            ring_verifier.verify_returning_error(ring_prover.prove(secret), result),
+            Err(RingProofError::DomainMismatch)
+        );
+    }
+
+    #[test]
+    fn test_extra_inputs() {
+        let rng = &mut test_rng();
+        let (pcs_params, piop_params) = setup::<_, KZG<Bls12_381>>(rng, 2usize.pow(9));
+        let pks = random_vec::<SWAffine, _>(10, rng);
+        let (prover_key, verifier_key) =
+            index::<_, KZG<Bls12_381>, _>(&pcs_params, &piop_params, &pks);
+
+        let secret = Fr::rand(rng);
+        let result = (piop_params.h.mul(secret) + pks[0]).into_affine();
+        let ring_prover = RingProver::builder()
+            .with_prover_key(prover_key)
+            .with_piop_params(piop_params.clone())
+            .with_index(0)
+            .with_transcript(ArkTranscript::new(b"ring-vrf-test"))
+            .build()
+            .unwrap();
+        let ring_verifier = RingVerifier::builder()
+            .with_verifier_key(verifier_key)
+            .with_piop_params(piop_params)
+            .with_transcript(ArkTranscript::new(b"ring-vrf-test"))
+            .build()
+            .unwrap();
+
+        let extra_inputs = random_vec::<SWAffine, _>(2, rng);
+        let proof = || ring_prover.prove_with_extra_inputs(secret, &extra_inputs);
+        assert_eq!(
+            ring_verifier.verify_with_extra_inputs(proof(), result, &extra_inputs),
+            Ok(())
+        );
+        let swapped = [extra_inputs[1], extra_inputs[0]];
+        assert_eq!(
+            ring_verifier.verify_with_extra_inputs(proof(), result, &swapped),
+            Err(RingProofError::KzgVerificationFailed)
+        );
+        assert!(!ring_verifier.verify_ring_proof(proof(), result));
+        assert_eq!(
+            ring_verifier.verify_with_extra_inputs(ring_prover.prove(secret), result, &[]),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_verify_batch() {
+        let rng = &mut test_rng();
+        let (pcs_params, piop_params) = setup::<_, KZG<Bls12_381>>(rng, 2usize.pow(9));
+        let pks = random_vec::<SWAffine, _>(10, rng);
+        let (prover_key, verifier_key) =
+            index::<_, KZG<Bls12_381>, _>(&pcs_params, &piop_params, &pks);
+
+        let ring_prover = RingProver::builder()
+            .with_prover_key(prover_key)
+            .with_piop_params(piop_params.clone())
+            .with_index(0)
+            .with_transcript(ArkTranscript::new(b"ring-vrf-test"))
+            .build()
+            .unwrap();
+        let secrets = random_vec::<Fr, _>(3, rng);
+        let proofs = || {
+            secrets
+                .iter()
+                .map(|&secret| {
+                    let result = (piop_params.h.mul(secret) + pks[0]).into_affine();
+                    (ring_prover.prove(secret), result)
+                })
+                .collect::<Vec<_>>()
+        };
+        let ring_verifier = RingVerifier::builder()
+            .with_verifier_key(verifier_key)
+            .with_piop_params(piop_params.clone())
+            .with_transcript(ArkTranscript::new(b"ring-vrf-test"))
+            .build()
+            .unwrap();
+
+        assert!(ring_verifier.verify_batch(proofs(), rng));
+        let mut invalid_proofs = proofs();
+        invalid_proofs[1].1 = pks[0];
+        assert!(!ring_verifier.verify_batch(invalid_proofs, rng));
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn test_prove_async() {
+        let rng = &mut test_rng();
+        let (pcs_params, piop_params) = setup::<_, KZG<Bls12_381>>(rng, 2usize.pow(9));
+        let pks = random_vec::<SWAffine, _>(10, rng);
+        let (prover_key, verifier_key) =
+            index::<_, KZG<Bls12_381>, _>(&pcs_params, &piop_params, &pks);
+
+        let secret = Fr::rand(rng);
+        let result = piop_params.h.mul(secret) + pks[0];
+        let ring_prover = RingProver::builder()
+            .with_prover_key(prover_key)
+            .with_piop_params(piop_params.clone())
+            .with_index(0)
+            .with_transcript(ArkTranscript::new(b"ring-vrf-test"))
+            .build()
+            .unwrap();
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let proof = runtime.block_on(ring_prover.prove_async(secret)).unwrap();
+
+        let ring_verifier = RingVerifier::builder()
+            .with_verifier_key(verifier_key)
+            .with_piop_params(piop_params)
+            .with_transcript(ArkTranscript::new(b"ring-vrf-test"))
+            .build()
+            .unwrap();
+        assert!(ring_verifier.verify_ring_proof(proof, result.into_affine()));
     }
 
     #[test]
@@ -146,13 +398,18 @@ mod tests {
         let domain_size = 2usize.pow(9);
 
         let (pcs_params, piop_params) = setup::<_, KZG<Bls12_381>>(rng, domain_size);
-        let ring_builder_key = RingBuilderKey::from_srs(&pcs_params, domain_size);
+        let ring_builder_key = RingBuilderKey::from_srs(&pcs_params, domain_size).unwrap();
 
         let max_keyset_size = piop_params.keyset_part_size;
         let keyset_size: usize = rng.gen_range(0..max_keyset_size);
         let pks = random_vec::<SWAffine, _>(keyset_size, rng);
 
-        let (_, verifier_key) = index::<_, KZG<Bls12_381>, _>(&pcs_params, &piop_params, &pks);
+        let (prover_key, verifier_key) =
+            index::<_, KZG<Bls12_381>, _>(&pcs_params, &piop_params, &pks);
+        assert_eq!(
+            prover_key.verifier_key().fixed_columns_committed,
+            verifier_key.fixed_columns_committed
+        );
 
         let ring = Ring::<_, Bls12_381, _>::with_keys(&piop_params, &pks, &ring_builder_key);
 
@@ -161,6 +418,27 @@ mod tests {
             fixed_columns_committed,
             verifier_key.fixed_columns_committed
         );
+
+        let ring_verifier_key = ring.into_verifier_key(pcs_params.raw_vk());
+        assert_eq!(ring_verifier_key.domain_size(), domain_size);
+        assert_eq!(ring_verifier_key.domain_size(), verifier_key.domain_size());
+        assert_eq!(ring_verifier_key.hiding(), verifier_key.hiding());
+        assert_eq!(ring_verifier_key.short_hash(), verifier_key.short_hash());
+        assert_eq!(verifier_key.display_short().len(), 16);
+    }
+
+    #[test]
+    fn test_minimal_domain_size_for_keyset() {
+        let rng = &mut test_rng();
+        let h = SWAffine::rand(rng);
+        let seed = find_complement_point::<BandersnatchConfig>();
+        for keyset_size in [1, 255, 256, 1000] {
+            let domain_size = minimal_domain_size_for_keyset::<SWAffine>(keyset_size);
+            let piop_params = PiopParams::setup(Domain::new(domain_size, true), h, seed);
+            assert!(piop_params.keyset_part_size >= keyset_size);
+            let smaller_domain = Domain::<Fq>::new(domain_size / 2, true);
+            assert!(smaller_domain.capacity < keyset_size + piop_params.scalar_bitlen + 1);
+        }
     }
 
     fn setup<R: Rng, CS: PCS<Fq>>(
@@ -194,9 +472,45 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_builder_errors() {
+        let rng = &mut test_rng();
+        let (pcs_params, piop_params) = setup::<_, KZG<Bls12_381>>(rng, 2usize.pow(9));
+        let (prover_key, _) = index::<_, KZG<Bls12_381>, _>(&pcs_params, &piop_params, &[]);
+
+        let builder = RingProver::builder()
+            .with_prover_key(prover_key)
+            .with_piop_params(piop_params.clone())
+            .with_transcript(ArkTranscript::new(b"ring-vrf-test"));
+        assert!(matches!(builder.build(), Err(BuilderError::MissingIndex)));
+
+        let verifier_builder = RingVerifier::<_, KZG<Bls12_381>, _, ArkTranscript>::builder()
+            .with_piop_params(piop_params);
+        assert!(matches!(
+            verifier_builder.build(),
+            Err(BuilderError::MissingVerifierKey)
+        ));
+    }
+
     #[test]
     fn test_ring_proof_kzg() {
-        _test_ring_proof::<KZG<Bls12_381>>(2usize.pow(10));
+        let proof_size = _test_ring_proof::<KZG<Bls12_381>>(2usize.pow(10));
+        let size_estimate = ring_proof_size_estimate::<Fq, KZG<Bls12_381>>(48, 32, 48);
+        assert_eq!(proof_size, size_estimate);
+        let domain = Domain::new(2usize.pow(10), true);
+        let piop_params = PiopParams::<Fq, BandersnatchConfig>::setup(
+            domain,
+            SWAffine::generator(),
+            find_complement_point::<BandersnatchConfig>(),
+        );
+        assert_eq!(
+            piop_params.bytes_per_proof::<KZG<Bls12_381>>(),
+            Some(size_estimate)
+        );
+        assert_eq!(
+            piop_params.bytes_per_proof::<fflonk::pcs::IdentityCommitment>(),
+            None
+        );
     }
 
     #[test]
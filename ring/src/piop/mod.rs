@@ -2,8 +2,11 @@ use ark_ec::pairing::Pairing;
 use ark_ec::short_weierstrass::{Affine, SWCurveConfig};
 use ark_ec::AffineRepr;
 use ark_ff::PrimeField;
+use ark_poly::EvaluationDomain;
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::fmt::Write;
 use ark_std::marker::PhantomData;
+use ark_std::string::String;
 use ark_std::{vec, vec::Vec};
 use fflonk::pcs::kzg::commitment::KzgCommitment;
 use fflonk::pcs::kzg::params::RawKzgVerifierKey;
@@ -31,6 +34,8 @@ pub struct RingCommitments<F: PrimeField, C: Commitment<F>> {
 }
 
 impl<F: PrimeField, C: Commitment<F>> ColumnsCommited<F, C> for RingCommitments<F, C> {
+    const N: usize = 4;
+
     fn to_vec(self) -> Vec<C> {
         vec![
             self.bits,
@@ -51,6 +56,8 @@ pub struct RingEvaluations<F: PrimeField> {
 }
 
 impl<F: PrimeField> ColumnsEvaluated<F> for RingEvaluations<F> {
+    const N: usize = 7;
+
     fn to_vec(self) -> Vec<F> {
         vec![
             self.points[0],
@@ -64,6 +71,16 @@ impl<F: PrimeField> ColumnsEvaluated<F> for RingEvaluations<F> {
     }
 }
 
+// The part of a ring proof other than the column commitments and the column evaluations,
+// for the protocols that transmit those separately.
+#[derive(Clone, CanonicalSerialize, CanonicalDeserialize)]
+pub struct RingOpenings<F: PrimeField, CS: PCS<F>> {
+    pub quotient_commitment: CS::C,
+    pub lin_at_zeta_omega: F,
+    pub agg_at_zeta_proof: CS::Proof,
+    pub lin_at_zeta_omega_proof: CS::Proof,
+}
+
 // Columns commitment to which the verifier knows (or trusts).
 #[derive(Clone, CanonicalSerialize, CanonicalDeserialize)]
 pub struct FixedColumns<F: PrimeField, G: AffineRepr<BaseField = F>> {
@@ -132,11 +149,50 @@ pub struct ProverKey<F: PrimeField, CS: PCS<F>, G: AffineRepr<BaseField = F>> {
     pub(crate) verifier_key: VerifierKey<F, CS>, // used in the Fiat-Shamir transform
 }
 
+impl<F: PrimeField, CS: PCS<F>, G: AffineRepr<BaseField = F>> ProverKey<F, CS, G> {
+    // The verifier key embedded for the Fiat-Shamir transform, accessible without cloning the prover key.
+    pub fn verifier_key(&self) -> &VerifierKey<F, CS> {
+        &self.verifier_key
+    }
+}
+
 #[derive(Clone, Debug, Eq, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
 pub struct VerifierKey<F: PrimeField, CS: PCS<F>> {
     pub(crate) pcs_raw_vk: <CS::Params as PcsParams>::RVK,
     pub(crate) fixed_columns_committed: FixedColumnsCommitted<F, CS::C>,
-    //TODO: domain
+    pub(crate) domain_size: usize,
+    pub(crate) hiding: bool,
+}
+
+impl<F: PrimeField, CS: PCS<F>> VerifierKey<F, CS> {
+    // Size of the domain the columns are committed over, required to set up the matching `PiopParams`.
+    pub fn domain_size(&self) -> usize {
+        self.domain_size
+    }
+
+    pub fn hiding(&self) -> bool {
+        self.hiding
+    }
+
+    // Identifies the ring by the first 8 bytes of the Blake2b hash of the fixed columns commitment.
+    // Meant for logs and the like: 64 bits give no collision resistance to rely on for security.
+    pub fn short_hash(&self) -> [u8; 8] {
+        use blake2::Digest;
+        let mut bytes = vec![];
+        self.fixed_columns_committed
+            .serialize_compressed(&mut bytes)
+            .unwrap();
+        let hash = blake2::Blake2b512::digest(&bytes);
+        hash[..8].try_into().unwrap()
+    }
+
+    // `short_hash` as a hex string.
+    pub fn display_short(&self) -> String {
+        self.short_hash().iter().fold(String::new(), |mut s, b| {
+            write!(s, "{b:02x}").unwrap();
+            s
+        })
+    }
 }
 
 impl<E: Pairing> VerifierKey<E::ScalarField, KZG<E>> {
@@ -144,16 +200,26 @@ impl<E: Pairing> VerifierKey<E::ScalarField, KZG<E>> {
         ring: &Ring<E::ScalarField, E, G>,
         kzg_vk: RawKzgVerifierKey<E>,
     ) -> Self {
-        Self::from_commitment_and_kzg_vk(FixedColumnsCommitted::from_ring(ring), kzg_vk)
+        // `Ring` always reserves the zero-knowledge rows, so the domain is hiding.
+        Self::from_commitment_and_kzg_vk(
+            FixedColumnsCommitted::from_ring(ring),
+            kzg_vk,
+            ring.domain_size,
+            true,
+        )
     }
 
     pub fn from_commitment_and_kzg_vk(
         commitment: FixedColumnsCommitted<E::ScalarField, KzgCommitment<E>>,
         kzg_vk: RawKzgVerifierKey<E>,
+        domain_size: usize,
+        hiding: bool,
     ) -> Self {
         Self {
             pcs_raw_vk: kzg_vk,
             fixed_columns_committed: commitment,
+            domain_size,
+            hiding,
         }
     }
 
@@ -169,11 +235,15 @@ pub fn index<F: PrimeField, CS: PCS<F>, Curve: SWCurveConfig<BaseField = F>>(
 ) -> (ProverKey<F, CS, Affine<Curve>>, VerifierKey<F, CS>) {
     let pcs_ck = pcs_params.ck();
     let pcs_raw_vk = pcs_params.raw_vk();
-    let fixed_columns = piop_params.fixed_columns(&keys);
+    let fixed_columns = piop_params.fixed_columns(keys);
     let fixed_columns_committed = fixed_columns.commit::<CS>(&pcs_ck);
+    let domain_size = piop_params.domain.domain().size();
+    let hiding = piop_params.domain.hiding;
     let verifier_key = VerifierKey {
         pcs_raw_vk: pcs_raw_vk.clone(),
         fixed_columns_committed: fixed_columns_committed.clone(),
+        domain_size,
+        hiding,
     };
     let prover_key = ProverKey {
         pcs_ck,
@@ -183,6 +253,62 @@ pub fn index<F: PrimeField, CS: PCS<F>, Curve: SWCurveConfig<BaseField = F>>(
     let verifier_key = VerifierKey {
         pcs_raw_vk,
         fixed_columns_committed,
+        domain_size,
+        hiding,
     };
     (prover_key, verifier_key)
 }
+
+#[cfg(test)]
+mod tests {
+    use ark_bls12_381::{Bls12_381, Fr};
+    use ark_ed_on_bls12_381_bandersnatch::SWAffine;
+    use ark_std::{test_rng, UniformRand};
+
+    use common::domain::Domain;
+    use common::test_helpers::random_vec;
+
+    use super::*;
+
+    #[test]
+    fn test_fixed_columns_serialization() {
+        let rng = &mut test_rng();
+        let domain_size = 1 << 9;
+        let pcs_params = KZG::<Bls12_381>::setup(domain_size, rng);
+        let domain = Domain::new(domain_size, true);
+        let piop_params = PiopParams::setup(domain, SWAffine::rand(rng), SWAffine::rand(rng));
+        let keys = random_vec::<SWAffine, _>(10, rng);
+
+        let fixed_columns = piop_params.fixed_columns(&keys);
+        let mut buf = vec![];
+        fixed_columns.serialize_compressed(&mut buf).unwrap();
+        let res = FixedColumns::<Fr, SWAffine>::deserialize_compressed(&buf[..]).unwrap();
+
+        let ck = pcs_params.ck();
+        assert_eq!(
+            res.commit::<KZG<Bls12_381>>(&ck),
+            fixed_columns.commit::<KZG<Bls12_381>>(&ck)
+        );
+    }
+
+    #[test]
+    fn test_verifier_key_serialization() {
+        let rng = &mut test_rng();
+        let domain_size = 1 << 9;
+        let pcs_params = KZG::<Bls12_381>::setup(domain_size, rng);
+        let domain = Domain::new(domain_size, false);
+        let piop_params = PiopParams::setup(domain, SWAffine::rand(rng), SWAffine::rand(rng));
+        let keys = random_vec::<SWAffine, _>(10, rng);
+
+        let (_, verifier_key) = index::<_, KZG<Bls12_381>, _>(&pcs_params, &piop_params, &keys);
+        let mut buf = vec![];
+        verifier_key.serialize_compressed(&mut buf).unwrap();
+        let res = VerifierKey::<Fr, KZG<Bls12_381>>::deserialize_compressed(&buf[..]).unwrap();
+        assert_eq!(
+            res.fixed_columns_committed,
+            verifier_key.fixed_columns_committed
+        );
+        assert_eq!(res.domain_size(), domain_size);
+        assert!(!res.hiding());
+    }
+}
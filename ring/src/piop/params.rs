@@ -1,7 +1,13 @@
-use ark_ec::short_weierstrass::{Affine, SWCurveConfig};
+use ark_ec::short_weierstrass::{Affine, Projective, SWCurveConfig};
 use ark_ec::{AdditiveGroup, AffineRepr, CurveGroup};
 use ark_ff::{BigInteger, PrimeField};
+use ark_poly::EvaluationDomain;
+use ark_serialize::{
+    CanonicalDeserialize, CanonicalSerialize, Compress, Read, SerializationError, Valid, Validate,
+    Write,
+};
 use ark_std::{vec, vec::Vec};
+use fflonk::pcs::PCS;
 
 use common::domain::Domain;
 use common::gadgets::sw_cond_add::AffineColumn;
@@ -32,8 +38,20 @@ pub struct PiopParams<F: PrimeField, Curve: SWCurveConfig<BaseField = F>> {
 
 impl<F: PrimeField, Curve: SWCurveConfig<BaseField = F>> PiopParams<F, Curve> {
     pub fn setup(domain: Domain<F>, h: Affine<Curve>, seed: Affine<Curve>) -> Self {
-        let padding_point = crate::hash_to_curve(b"/w3f/ring-proof/padding");
         let scalar_bitlen = Curve::ScalarField::MODULUS_BIT_SIZE as usize;
+        Self::setup_with_scalar_bitlen(domain, h, seed, scalar_bitlen)
+    }
+
+    // For the curves where the scalars used are known to fit into less bits than the scalar field modulus.
+    // The keyset part of the columns is extended by the bits saved.
+    pub fn setup_with_scalar_bitlen(
+        domain: Domain<F>,
+        h: Affine<Curve>,
+        seed: Affine<Curve>,
+        scalar_bitlen: usize,
+    ) -> Self {
+        assert!(scalar_bitlen <= Curve::ScalarField::MODULUS_BIT_SIZE as usize);
+        let padding_point = crate::hash_to_curve(b"/w3f/ring-proof/padding");
         // 1 accounts for the last cells of the points and bits columns that remain unconstrained
         let keyset_part_size = domain.capacity - scalar_bitlen - 1;
         Self {
@@ -47,22 +65,41 @@ impl<F: PrimeField, Curve: SWCurveConfig<BaseField = F>> PiopParams<F, Curve> {
     }
 
     pub fn fixed_columns(&self, keys: &[Affine<Curve>]) -> FixedColumns<F, Affine<Curve>> {
+        assert!(
+            self.check_seed_not_equal_to_key(keys),
+            "the seed is in the ring"
+        );
         let ring_selector = self.keyset_part_selector();
         let ring_selector = self.domain.public_column(ring_selector);
-        let points = self.points_column(&keys);
+        let points = self.points_column(keys);
         FixedColumns {
             points,
             ring_selector,
         }
     }
 
+    // The conditional addition gadget relies on the seed being distinct from the keys,
+    // that holds for the keys from the prime-order subgroup, but is worth checking at setup.
+    pub fn check_seed_not_equal_to_key(&self, keys: &[Affine<Curve>]) -> bool {
+        !keys.contains(&self.seed)
+    }
+
     pub fn points_column(&self, keys: &[Affine<Curve>]) -> AffineColumn<F, Affine<Curve>> {
         assert!(keys.len() <= self.keyset_part_size);
-        let padding_len = self.keyset_part_size - keys.len();
-        let padding = vec![self.padding_point; padding_len];
-        let points = [keys, &padding, &self.power_of_2_multiples_of_h()].concat();
-        assert_eq!(points.len(), self.domain.capacity - 1);
-        AffineColumn::public_column(points, &self.domain)
+        let powers_of_h = self.power_of_2_multiples_of_h();
+        assert_eq!(
+            self.keyset_part_size + powers_of_h.len(),
+            self.domain.capacity - 1
+        );
+        AffineColumn::from_fn_public(
+            self.domain.capacity - 1,
+            |i| match i {
+                i if i < keys.len() => keys[i],
+                i if i < self.keyset_part_size => self.padding_point,
+                i => powers_of_h[i - self.keyset_part_size],
+            },
+            &self.domain,
+        )
     }
 
     pub fn power_of_2_multiples_of_h(&self) -> Vec<Affine<Curve>> {
@@ -76,12 +113,54 @@ impl<F: PrimeField, Curve: SWCurveConfig<BaseField = F>> PiopParams<F, Curve> {
         CurveGroup::normalize_batch(&multiples)
     }
 
+    // Computes `scalar.H` as the sum of the power-of-2 multiples of `H` selected by the bits of the scalar,
+    // the same way it is done in the circuit.
+    pub fn scalar_multiple_of_h(&self, scalar: Curve::ScalarField) -> Affine<Curve> {
+        self.scalar_part(scalar)
+            .iter()
+            .zip(self.power_of_2_multiples_of_h())
+            .filter(|(&b, _)| b)
+            .map(|(_, p)| p)
+            .sum::<Projective<Curve>>()
+            .into_affine()
+    }
+
     pub fn scalar_part(&self, e: Curve::ScalarField) -> Vec<bool> {
         let bits_with_trailing_zeroes = e.into_bigint().to_bits_le();
-        let significant_bits = &bits_with_trailing_zeroes[..self.scalar_bitlen];
+        let (significant_bits, zeroes) = bits_with_trailing_zeroes.split_at(self.scalar_bitlen);
+        assert!(
+            zeroes.iter().all(|b| !b),
+            "scalar exceeds {} bits",
+            self.scalar_bitlen
+        );
         significant_bits.to_vec()
     }
 
+    pub fn scalar_bitlen(&self) -> usize {
+        self.scalar_bitlen
+    }
+
+    // Size of a serialized `RingProof<F, CS>`, computed from the sizes of the default elements of the PCS.
+    // Returns `None` for the schemes with empty opening proofs, as `IdentityCommitment`,
+    // as their commitments are the polynomials themselves and have no fixed size.
+    pub fn bytes_per_proof<CS: PCS<F>>(&self) -> Option<usize>
+    where
+        CS::Proof: Default,
+    {
+        let opening_proof_size = CS::Proof::default().compressed_size();
+        if opening_proof_size == 0 {
+            return None;
+        }
+        let zero_commitment: CS::C = ark_std::iter::empty().sum();
+        let commitment_size = zero_commitment.compressed_size();
+        let fr_size = F::zero().compressed_size();
+        Some(crate::ring_proof_size_estimate::<F, CS>(
+            commitment_size,
+            fr_size,
+            opening_proof_size,
+        ))
+    }
+
     pub fn keyset_part_selector(&self) -> Vec<F> {
         [
             vec![F::one(); self.keyset_part_size],
@@ -91,14 +170,96 @@ impl<F: PrimeField, Curve: SWCurveConfig<BaseField = F>> PiopParams<F, Curve> {
     }
 }
 
+// The domain is serialized as its size and the hiding flag, and is recomputed on deserialization.
+impl<F: PrimeField, Curve: SWCurveConfig<BaseField = F>> CanonicalSerialize
+    for PiopParams<F, Curve>
+{
+    fn serialize_with_mode<W: Write>(
+        &self,
+        mut writer: W,
+        compress: Compress,
+    ) -> Result<(), SerializationError> {
+        self.domain
+            .domain()
+            .size()
+            .serialize_with_mode(&mut writer, compress)?;
+        self.domain
+            .hiding
+            .serialize_with_mode(&mut writer, compress)?;
+        self.scalar_bitlen
+            .serialize_with_mode(&mut writer, compress)?;
+        self.h.serialize_with_mode(&mut writer, compress)?;
+        self.seed.serialize_with_mode(&mut writer, compress)?;
+        self.padding_point
+            .serialize_with_mode(&mut writer, compress)?;
+        Ok(())
+    }
+
+    fn serialized_size(&self, compress: Compress) -> usize {
+        self.domain.domain().size().serialized_size(compress)
+            + self.domain.hiding.serialized_size(compress)
+            + self.scalar_bitlen.serialized_size(compress)
+            + self.h.serialized_size(compress)
+            + self.seed.serialized_size(compress)
+            + self.padding_point.serialized_size(compress)
+    }
+}
+
+impl<F: PrimeField, Curve: SWCurveConfig<BaseField = F>> Valid for PiopParams<F, Curve> {
+    fn check(&self) -> Result<(), SerializationError> {
+        self.h.check()?;
+        self.seed.check()?;
+        self.padding_point.check()
+    }
+}
+
+impl<F: PrimeField, Curve: SWCurveConfig<BaseField = F>> CanonicalDeserialize
+    for PiopParams<F, Curve>
+{
+    fn deserialize_with_mode<R: Read>(
+        mut reader: R,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        let domain_size = usize::deserialize_with_mode(&mut reader, compress, validate)?;
+        let hiding = bool::deserialize_with_mode(&mut reader, compress, validate)?;
+        let scalar_bitlen = usize::deserialize_with_mode(&mut reader, compress, validate)?;
+        let h = Affine::<Curve>::deserialize_with_mode(&mut reader, compress, validate)?;
+        let seed = Affine::<Curve>::deserialize_with_mode(&mut reader, compress, validate)?;
+        let padding_point =
+            Affine::<Curve>::deserialize_with_mode(&mut reader, compress, validate)?;
+        // Otherwise `Domain::new` or the keyset part size computation would panic.
+        if !domain_size.is_power_of_two()
+            || domain_size <= scalar_bitlen + common::domain::ZK_ROWS + 1
+            || scalar_bitlen > Curve::ScalarField::MODULUS_BIT_SIZE as usize
+        {
+            return Err(SerializationError::InvalidData);
+        }
+        let domain =
+            Domain::new_checked(domain_size, hiding).ok_or(SerializationError::InvalidData)?;
+        let keyset_part_size = domain.capacity - scalar_bitlen - 1;
+        Ok(Self {
+            domain,
+            scalar_bitlen,
+            keyset_part_size,
+            h,
+            seed,
+            padding_point,
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use ark_ed_on_bls12_381_bandersnatch::{BandersnatchConfig, Fq, Fr, SWAffine};
+    use ark_ff::PrimeField;
+    use ark_poly::EvaluationDomain;
+    use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
     use ark_std::ops::Mul;
     use ark_std::{test_rng, UniformRand};
 
     use common::domain::Domain;
-    use common::test_helpers::cond_sum;
+    use common::test_helpers::{cond_sum, random_vec};
 
     use crate::piop::params::PiopParams;
 
@@ -113,5 +274,58 @@ mod tests {
         let t_bits = params.scalar_part(t);
         let th = cond_sum(&t_bits, &params.power_of_2_multiples_of_h());
         assert_eq!(th, params.h.mul(t));
+        assert_eq!(params.scalar_multiple_of_h(t), th);
+        assert!(params.check_seed_not_equal_to_key(&[h]));
+        assert!(!params.check_seed_not_equal_to_key(&[h, seed]));
+    }
+
+    #[test]
+    fn test_serialization() {
+        let rng = &mut test_rng();
+        let h = SWAffine::rand(rng);
+        let seed = SWAffine::rand(rng);
+        let domain = Domain::new(1024, true);
+        let params =
+            PiopParams::<Fq, BandersnatchConfig>::setup_with_scalar_bitlen(domain, h, seed, 200);
+
+        let mut buf = vec![];
+        params.serialize_compressed(&mut buf).unwrap();
+        assert_eq!(buf.len(), params.compressed_size());
+        let res = PiopParams::<Fq, BandersnatchConfig>::deserialize_compressed(&buf[..]).unwrap();
+        assert_eq!(res.domain.domain().size(), 1024);
+        assert!(res.domain.hiding);
+        assert_eq!(res.scalar_bitlen, params.scalar_bitlen);
+        assert_eq!(res.keyset_part_size, params.keyset_part_size);
+        assert_eq!(res.h, params.h);
+        assert_eq!(res.seed, params.seed);
+        assert_eq!(res.padding_point, params.padding_point);
+    }
+
+    #[test]
+    fn test_custom_scalar_bitlen() {
+        let rng = &mut test_rng();
+        let h = SWAffine::rand(rng);
+        let seed = SWAffine::rand(rng);
+        let domain = Domain::new(1024, false);
+        let default_params = PiopParams::<Fq, BandersnatchConfig>::setup(domain.clone(), h, seed);
+        let scalar_bitlen = 200;
+        let params = PiopParams::<Fq, BandersnatchConfig>::setup_with_scalar_bitlen(
+            domain,
+            h,
+            seed,
+            scalar_bitlen,
+        );
+        assert_eq!(params.scalar_bitlen(), scalar_bitlen);
+        assert_eq!(
+            params.keyset_part_size,
+            default_params.keyset_part_size + default_params.scalar_bitlen() - scalar_bitlen
+        );
+        for _ in 0..10 {
+            let t = Fr::from_le_bytes_mod_order(&random_vec::<u8, _>(scalar_bitlen / 8, rng));
+            let t_bits = params.scalar_part(t);
+            assert_eq!(t_bits.len(), scalar_bitlen);
+            let th = cond_sum(&t_bits, &params.power_of_2_multiples_of_h());
+            assert_eq!(th, params.h.mul(t));
+        }
     }
 }
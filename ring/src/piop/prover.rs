@@ -2,16 +2,18 @@ use ark_ec::short_weierstrass::{Affine, SWCurveConfig};
 use ark_ff::PrimeField;
 use ark_poly::univariate::DensePolynomial;
 use ark_poly::Evaluations;
+use ark_serialize::CanonicalSerialize;
 use ark_std::marker::PhantomData;
+use ark_std::rand::Rng;
+#[cfg(feature = "parallel")]
+use ark_std::rand::{rngs::StdRng, SeedableRng};
 use ark_std::{vec, vec::Vec};
 use fflonk::pcs::Commitment;
 
 use common::domain::Domain;
-use common::gadgets::booleanity::{BitColumn, Booleanity};
-use common::gadgets::fixed_cells::FixedCells;
-use common::gadgets::inner_prod::InnerProd;
-use common::gadgets::sw_cond_add::{AffineColumn, CondAdd};
-use common::gadgets::ProverGadget;
+use common::gadgets::{
+    AffineColumn, BitColumn, Booleanity, CondAdd, FixedCells, InnerProd, ProverGadget,
+};
 use common::piop::ProverPiop;
 use common::{Column, FieldColumn};
 
@@ -35,27 +37,62 @@ pub struct PiopProver<F: PrimeField, Curve: SWCurveConfig<BaseField = F>> {
     cond_add: CondAdd<F, Affine<Curve>>,
     cond_add_acc_x: FixedCells<F>,
     cond_add_acc_y: FixedCells<F>,
+    // Public inputs the proof is bound to in addition to the result.
+    extra_inputs: Vec<Affine<Curve>>,
 }
 
 impl<F: PrimeField, Curve: SWCurveConfig<BaseField = F>> PiopProver<F, Curve> {
-    pub fn build(
+    // `rng` is the source of the randomness for blinding the private columns.
+    pub fn build<R: Rng>(
         params: &PiopParams<F, Curve>,
         fixed_columns: FixedColumns<F, Affine<Curve>>,
         prover_index_in_keys: usize,
         secret: Curve::ScalarField,
+        rng: &mut R,
     ) -> Self {
         let domain = params.domain.clone();
         let FixedColumns {
             points,
             ring_selector,
         } = fixed_columns;
-        let bits = Self::bits_column(&params, prover_index_in_keys, secret);
-        let inner_prod = InnerProd::init(ring_selector.clone(), bits.col.clone(), &domain);
-        let cond_add = CondAdd::init(bits.clone(), points.clone(), params.seed, &domain);
+        let bits = Self::bits_column(params, prover_index_in_keys, secret, rng);
+        // The accumulators of the inner product and the conditional addition are independent,
+        // so with the `parallel` feature they are computed concurrently, each blinded with its own rng seeded from `rng`.
+        // Otherwise, `rng` is used directly, so nothing is drawn from it for a non-hiding domain.
+        #[cfg(feature = "parallel")]
+        let (inner_prod, cond_add) = {
+            let mut inner_prod_rng = StdRng::from_rng(&mut *rng).unwrap();
+            let mut cond_add_rng = StdRng::from_rng(&mut *rng).unwrap();
+            rayon::join(
+                || {
+                    InnerProd::init_with_rng(
+                        ring_selector.clone(),
+                        bits.col.clone(),
+                        &domain,
+                        &mut inner_prod_rng,
+                    )
+                },
+                || {
+                    CondAdd::init_with_rng(
+                        bits.clone(),
+                        points.clone(),
+                        params.seed,
+                        &domain,
+                        &mut cond_add_rng,
+                    )
+                },
+            )
+        };
+        #[cfg(not(feature = "parallel"))]
+        let (inner_prod, cond_add) = (
+            InnerProd::init_with_rng(ring_selector.clone(), bits.col.clone(), &domain, rng),
+            CondAdd::init_with_rng(bits.clone(), points.clone(), params.seed, &domain, rng),
+        );
+        debug_assert!(bits.is_valid());
         let booleanity = Booleanity::init(bits.clone());
         let cond_add_acc_x = FixedCells::init(cond_add.acc.xs.clone(), &domain);
         let cond_add_acc_y = FixedCells::init(cond_add.acc.ys.clone(), &domain);
-        let inner_prod_acc = FixedCells::init(inner_prod.acc.clone(), &domain);
+        let inner_prod_acc = FixedCells::from_inner_prod(&inner_prod, F::one(), &domain);
         Self {
             domain,
             points,
@@ -67,20 +104,27 @@ impl<F: PrimeField, Curve: SWCurveConfig<BaseField = F>> PiopProver<F, Curve> {
             booleanity,
             inner_prod,
             cond_add,
+            extra_inputs: vec![],
         }
     }
 
-    fn bits_column(
+    pub fn with_extra_inputs(mut self, extra_inputs: Vec<Affine<Curve>>) -> Self {
+        self.extra_inputs = extra_inputs;
+        self
+    }
+
+    fn bits_column<R: Rng>(
         params: &PiopParams<F, Curve>,
         index_in_keys: usize,
         secret: Curve::ScalarField,
+        rng: &mut R,
     ) -> BitColumn<F> {
         let mut keyset_part = vec![false; params.keyset_part_size];
         keyset_part[index_in_keys] = true;
         let scalar_part = params.scalar_part(secret);
         let bits = [keyset_part, scalar_part].concat();
         assert_eq!(bits.len(), params.domain.capacity - 1);
-        BitColumn::init(bits, &params.domain)
+        BitColumn::init_with_rng(bits, &params.domain, rng)
     }
 }
 
@@ -145,7 +189,7 @@ where
     }
 
     fn constraints(&self) -> Vec<Evaluations<F>> {
-        vec![
+        [
             self.inner_prod.constraints(),
             self.cond_add.constraints(),
             self.booleanity.constraints(),
@@ -156,8 +200,20 @@ where
         .concat()
     }
 
+    fn constraint_labels(&self) -> Vec<&'static str> {
+        [
+            self.inner_prod.constraint_labels(),
+            self.cond_add.constraint_labels(),
+            self.booleanity.constraint_labels(),
+            self.cond_add_acc_x.constraint_labels(),
+            self.cond_add_acc_y.constraint_labels(),
+            self.inner_prod_acc.constraint_labels(),
+        ]
+        .concat()
+    }
+
     fn constraints_lin(&self, zeta: &F) -> Vec<DensePolynomial<F>> {
-        vec![
+        [
             self.inner_prod.constraints_linearized(zeta),
             self.cond_add.constraints_linearized(zeta),
             self.booleanity.constraints_linearized(zeta),
@@ -175,4 +231,8 @@ where
     fn result(&self) -> Self::Instance {
         self.cond_add.result
     }
+
+    fn public_inputs(&self) -> Vec<impl CanonicalSerialize> {
+        [vec![self.cond_add.result], self.extra_inputs.clone()].concat()
+    }
 }
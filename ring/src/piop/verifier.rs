@@ -1,14 +1,14 @@
 use ark_ff::PrimeField;
-use ark_std::{vec, vec::Vec};
+use ark_std::vec::Vec;
 use fflonk::pcs::Commitment;
 
 use common::domain::EvaluatedDomain;
-use common::gadgets::booleanity::BooleanityValues;
-use common::gadgets::fixed_cells::FixedCellsValues;
-use common::gadgets::inner_prod::InnerProdValues;
-use common::gadgets::sw_cond_add::CondAddValues;
-use common::gadgets::VerifierGadget;
+use common::gadgets::{
+    BooleanityValues, CondAddCoeffs, CondAddValues, FixedCellsValues, InnerProdValues,
+    VerifierGadget,
+};
 use common::piop::VerifierPiop;
+use common::{ColumnsCommited, ColumnsEvaluated};
 
 use crate::piop::{FixedColumnsCommitted, RingCommitments};
 use crate::RingEvaluations;
@@ -59,29 +59,32 @@ impl<F: PrimeField, C: Commitment<F>> PiopVerifier<F, C> {
             bits: all_columns_evaluated.bits,
         };
 
-        let cond_add_acc_x = FixedCellsValues {
-            col: all_columns_evaluated.cond_add_acc[0],
-            col_first: init.0,
-            col_last: result.0,
-            l_first: domain_evals.l_first,
-            l_last: domain_evals.l_last,
-        };
-
-        let cond_add_acc_y = FixedCellsValues {
-            col: all_columns_evaluated.cond_add_acc[1],
-            col_first: init.1,
-            col_last: result.1,
-            l_first: domain_evals.l_first,
-            l_last: domain_evals.l_last,
-        };
-
-        let inner_prod_acc = FixedCellsValues {
-            col: all_columns_evaluated.inn_prod_acc,
-            col_first: F::zero(),
-            col_last: F::one(),
-            l_first: domain_evals.l_first,
-            l_last: domain_evals.l_last,
-        };
+        let cond_add_acc_x = FixedCellsValues::for_first_and_last(
+            all_columns_evaluated.cond_add_acc[0],
+            init.0,
+            result.0,
+            &domain_evals,
+        );
+
+        let cond_add_acc_y = FixedCellsValues::for_first_and_last(
+            all_columns_evaluated.cond_add_acc[1],
+            init.1,
+            result.1,
+            &domain_evals,
+        );
+
+        let inner_prod_acc = FixedCellsValues::for_first_and_last(
+            all_columns_evaluated.inn_prod_acc,
+            F::zero(),
+            F::one(),
+            &domain_evals,
+        );
+
+        debug_assert_eq!(
+            fixed_columns_committed.as_vec().len() + RingCommitments::<F, C>::N,
+            Self::N_COLUMNS
+        );
+        debug_assert_eq!(RingEvaluations::<F>::N, Self::N_COLUMNS);
 
         Self {
             domain_evals,
@@ -106,7 +109,7 @@ impl<F: PrimeField, C: Commitment<F>> VerifierPiop<F, C> for PiopVerifier<F, C>
     }
 
     fn evaluate_constraints_main(&self) -> Vec<F> {
-        vec![
+        [
             self.inner_prod.evaluate_constraints_main(),
             self.cond_add.evaluate_constraints_main(),
             self.booleanity.evaluate_constraints_main(),
@@ -117,7 +120,7 @@ impl<F: PrimeField, C: Commitment<F>> VerifierPiop<F, C> for PiopVerifier<F, C>
         .concat()
     }
 
-    fn constraint_polynomials_linearized_commitments(&self) -> Vec<C> {
+    fn linearized_constraint_commitment(&self, agg_coeffs: &[F]) -> C {
         let inner_prod_acc = self
             .witness_columns_committed
             .inn_prod_acc
@@ -131,7 +134,8 @@ impl<F: PrimeField, C: Commitment<F>> VerifierPiop<F, C> for PiopVerifier<F, C>
         let (c_acc_x, c_acc_y) = self.cond_add.acc_coeffs_2();
         let c2_lin = acc_x.mul(c_acc_x) + acc_y.mul(c_acc_y);
 
-        vec![inner_prod_acc, c1_lin, c2_lin]
+        // the constraints of the inner product and the conditional addition gadgets go first
+        C::combine(&agg_coeffs[..3], &[inner_prod_acc, c1_lin, c2_lin])
     }
 
     fn domain_evaluated(&self) -> &EvaluatedDomain<F> {
@@ -8,28 +8,30 @@ use ark_std::fmt;
 use ark_std::iter;
 use ark_std::ops::Range;
 use ark_std::vec::Vec;
+use fflonk::pcs::kzg::params::RawKzgVerifierKey;
 use fflonk::pcs::kzg::urs::URS;
+use fflonk::pcs::kzg::KZG;
 use fflonk::pcs::PcsParams;
 
 use common::domain::ZK_ROWS;
 
-use crate::PiopParams;
+use crate::{PiopParams, VerifierKey};
 
 const IDLE_ROWS: usize = ZK_ROWS + 1;
 
 /// Commitment to a list of VRF public keys as is used as a public input to the ring proof SNARK verifier.
-
+///
 /// The VRF keys are (inner) curve points that we represent in the affine short Weierstrass coordinates.
 /// We commit to the coordinate vectors independently using KZG on the outer curve. To make the commitment
 /// updatable we use SRS in the Lagrangian form: `L1, ..., Ln`, where `Li = L_i(t)G`.
 /// The commitment to a vector `a1, ..., an` is then `a1L1 + ... + anLn`.
-
+///
 /// We pad the list of keys with a `padding` point with unknown dlog up to a certain size.
 /// Additionally, to make the commitment compatible with the snark,
 /// we append the power-of-2 powers of the VRF blinding Pedersen base
 /// `H, 2H, 4H, ..., 2^(s-1)H`, where `s` is the bitness of the VRF curve scalar field.
 /// The last `IDLE_ROWS = 4` elements are set to `(0, 0)`.
-
+///
 /// Thus, the vector of points we commit to coordinatewise is
 /// `pk1, ..., pkn, padding, ..., padding, H, 2H, ..., 2^(s-1)H, 0, 0, 0, 0`
 
@@ -53,6 +55,8 @@ pub struct Ring<
     pub curr_keys: usize,
     // a parameter
     pub padding_point: Affine<VrfCurveConfig>,
+    // size of the domain the vector is committed over
+    pub domain_size: usize,
 }
 
 impl<
@@ -123,6 +127,7 @@ impl<
             max_keys: piop_params.keyset_part_size,
             curr_keys: 0,
             padding_point,
+            domain_size,
         }
     }
 
@@ -154,6 +159,99 @@ impl<
         self.curr_keys = new_size;
     }
 
+    // Replaces the `keys` at the `positions` with the padding point, as if they never were appended.
+    // `srs` should return `srs[range]` for `range = (i..i + 1)` for every position `i`.
+    // As the keys are appended after the last one, only the positions at the end of the ring are freed,
+    // the positions in the middle remain padded until the ring is rebuilt.
+    // The positions may come in any order, but each at most once.
+    pub fn remove(
+        &mut self,
+        keys: &[Affine<VrfCurveConfig>],
+        positions: &[usize],
+        srs: impl Fn(Range<usize>) -> Result<Vec<KzgCurve::G1Affine>, ()>,
+    ) {
+        assert_eq!(keys.len(), positions.len());
+        let mut removed: Vec<_> = positions.iter().copied().zip(keys).collect();
+        removed.sort_by_key(|(i, _)| *i);
+        assert!(
+            removed.windows(2).all(|w| w[0].0 < w[1].0),
+            "duplicate positions"
+        );
+        assert!(removed.iter().all(|(i, _)| *i < self.curr_keys));
+        let (padding_x, padding_y) = self.padding_point.xy().unwrap();
+        let (xs, ys): (Vec<F>, Vec<F>) = removed
+            .iter()
+            .map(|(_, p)| p.xy().unwrap())
+            .map(|(x, y)| (padding_x - x, padding_y - y))
+            .unzip();
+        let srs_segment: Vec<_> = removed
+            .iter()
+            .map(|&(i, _)| srs(i..i + 1).unwrap()[0])
+            .collect();
+        let cx_delta = KzgCurve::G1::msm(&srs_segment, &xs).unwrap();
+        let cy_delta = KzgCurve::G1::msm(&srs_segment, &ys).unwrap();
+
+        let (new_cx, new_cy) = {
+            let affine = KzgCurve::G1::normalize_batch(&[self.cx + cx_delta, self.cy + cy_delta]);
+            (affine[0], affine[1])
+        };
+
+        self.cx = new_cx;
+        self.cy = new_cy;
+        for &(i, _) in removed.iter().rev() {
+            if i + 1 != self.curr_keys {
+                break;
+            }
+            self.curr_keys -= 1;
+        }
+    }
+
+    // Replaces the key at `index` with `new_key` in place, so that the ring never shrinks.
+    // `srs_li` should be `srs[index]`. The selector and the number of keys stay the same.
+    pub fn rekey_at(
+        &mut self,
+        index: usize,
+        old_key: Affine<VrfCurveConfig>,
+        new_key: Affine<VrfCurveConfig>,
+        srs_li: KzgCurve::G1Affine,
+    ) {
+        assert!(index < self.curr_keys);
+        let (old_x, old_y) = old_key.xy().unwrap();
+        let (new_x, new_y) = new_key.xy().unwrap();
+        let (new_cx, new_cy) = {
+            let affine = KzgCurve::G1::normalize_batch(&[
+                self.cx + srs_li * (new_x - old_x),
+                self.cy + srs_li * (new_y - old_y),
+            ]);
+            (affine[0], affine[1])
+        };
+        self.cx = new_cx;
+        self.cy = new_cy;
+    }
+
+    // Commitments to the coordinates of each key in its own row, i.e. `(x_i * L_i, y_i * L_i)` for the key `(x_i, y_i)` at the index `i`.
+    // The ring commitment isn't the sum of these, as the rows that aren't occupied by the keys hold the other points,
+    // but it allows proving statements about the individual keys without the rest of the ring.
+    pub fn key_commitments(
+        piop_params: &PiopParams<F, VrfCurveConfig>,
+        keys: &[Affine<VrfCurveConfig>],
+        srs: &RingBuilderKey<F, KzgCurve>,
+    ) -> Vec<(KzgCurve::G1Affine, KzgCurve::G1Affine)> {
+        assert!(keys.len() <= piop_params.keyset_part_size);
+        let commitments: Vec<KzgCurve::G1> = keys
+            .iter()
+            .zip(&srs.lis_in_g1)
+            .flat_map(|(key, li)| {
+                let (x, y) = key.xy().unwrap();
+                [*li * x, *li * y]
+            })
+            .collect();
+        KzgCurve::G1::normalize_batch(&commitments)
+            .chunks(2)
+            .map(|c| (c[0], c[1]))
+            .collect()
+    }
+
     // Builds the ring from the keys provided with 2 MSMs of size `keys.len() + scalar_bitlen + 5`.
     // In some cases it may be beneficial to cash the empty ring, as updating it costs 2 MSMs of size `keys.len()`.
     pub fn with_keys(
@@ -177,7 +275,7 @@ impl<
             .chain(&powers_of_h)
             .map(|p| p.xy().unwrap())
             .map(|(x, y)| (x - padding_x, y - padding_y))
-            .chain(iter::repeat((-padding_x, -padding_y)).take(4))
+            .chain(iter::repeat_n((-padding_x, -padding_y), 4))
             .chain(iter::once((padding_x, padding_y)))
             .unzip();
 
@@ -189,8 +287,17 @@ impl<
         ]
         .concat();
 
-        let cx = KzgCurve::G1::msm(&bases, &xs).unwrap();
-        let cy = KzgCurve::G1::msm(&bases, &ys).unwrap();
+        // The MSMs for the coordinates are independent, so can be run concurrently.
+        #[cfg(feature = "parallel")]
+        let (cx, cy) = rayon::join(
+            || KzgCurve::G1::msm(&bases, &xs).unwrap(),
+            || KzgCurve::G1::msm(&bases, &ys).unwrap(),
+        );
+        #[cfg(not(feature = "parallel"))]
+        let (cx, cy) = (
+            KzgCurve::G1::msm(&bases, &xs).unwrap(),
+            KzgCurve::G1::msm(&bases, &ys).unwrap(),
+        );
         let selector_inv = srs.lis_in_g1[piop_params.keyset_part_size..]
             .iter()
             .sum::<KzgCurve::G1>();
@@ -208,9 +315,37 @@ impl<
             max_keys: piop_params.keyset_part_size,
             curr_keys: keys.len(),
             padding_point,
+            domain_size: piop_params.domain.domain().size(),
         }
     }
 
+    /// Builds the verifier key for the ring, when the ring is committed with KZG.
+    ///
+    /// ```
+    /// # use ark_bls12_381::Bls12_381;
+    /// # use ark_ed_on_bls12_381_bandersnatch::{BandersnatchConfig, SWAffine};
+    /// # use ark_std::{test_rng, UniformRand};
+    /// # use fflonk::pcs::kzg::KZG;
+    /// # use fflonk::pcs::{PcsParams, PCS};
+    /// # use ring::ring::{Ring, RingBuilderKey};
+    /// # use ring::{find_complement_point, Domain, PiopParams};
+    /// # let rng = &mut test_rng();
+    /// # let domain_size = 512;
+    /// # let domain = Domain::new(domain_size, true);
+    /// # let seed = find_complement_point::<BandersnatchConfig>();
+    /// # let piop_params = PiopParams::setup(domain, SWAffine::rand(rng), seed);
+    /// # let keys = vec![SWAffine::rand(rng); 10];
+    /// let urs = KZG::<Bls12_381>::setup(3 * domain_size, rng);
+    /// let ring = Ring::with_keys(&piop_params, &keys, &RingBuilderKey::from_srs(&urs, domain_size).unwrap());
+    /// let verifier_key = ring.into_verifier_key(urs.raw_vk());
+    /// ```
+    pub fn into_verifier_key(
+        self,
+        kzg_vk: RawKzgVerifierKey<KzgCurve>,
+    ) -> VerifierKey<F, KZG<KzgCurve>> {
+        VerifierKey::from_ring_and_kzg_vk(&self, kzg_vk)
+    }
+
     pub fn slots_left(&self) -> usize {
         self.max_keys - self.curr_keys
     }
@@ -231,6 +366,7 @@ impl<
             max_keys,
             curr_keys: 0,
             padding_point,
+            domain_size,
         }
     }
 }
@@ -244,14 +380,23 @@ pub struct RingBuilderKey<F: PrimeField, KzgCurve: Pairing<ScalarField = F>> {
 }
 
 impl<F: PrimeField, KzgCurve: Pairing<ScalarField = F>> RingBuilderKey<F, KzgCurve> {
-    pub fn from_srs(srs: &URS<KzgCurve>, domain_size: usize) -> Self {
+    // The SRS should contain at least `domain_size` powers in G1 to compute the Lagrangian basis.
+    pub fn from_srs(srs: &URS<KzgCurve>, domain_size: usize) -> Result<Self, SrsError> {
+        if domain_size > srs.powers_in_g1.len() {
+            return Err(SrsError::DomainSizeExceedsSrs);
+        }
         let g1 = srs.powers_in_g1[0].into_group();
         let ck = srs.ck_with_lagrangian(domain_size);
         let lis_in_g1 = ck.lagrangian.unwrap().lis_in_g;
-        Self { lis_in_g1, g1 }
+        Ok(Self { lis_in_g1, g1 })
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SrsError {
+    DomainSizeExceedsSrs,
+}
+
 #[cfg(test)]
 mod tests {
     use ark_bls12_381::{Bls12_381, Fr, G1Affine};
@@ -278,7 +423,7 @@ mod tests {
         let domain_size = 1 << 9;
 
         let pcs_params = KZG::<Bls12_381>::setup(domain_size - 1, rng);
-        let ring_builder_key = RingBuilderKey::from_srs(&pcs_params, domain_size);
+        let ring_builder_key = RingBuilderKey::from_srs(&pcs_params, domain_size).unwrap();
         let srs = |range: Range<usize>| Ok(ring_builder_key.lis_in_g1[range].to_vec());
 
         // piop params
@@ -300,6 +445,84 @@ mod tests {
 
         let same_ring = TestRing::with_keys(&piop_params, &keys, &ring_builder_key);
         assert_eq!(ring, same_ring);
+
+        // Removing the last keys frees their positions, whatever the order.
+        let mut ring_after_removal = ring.clone();
+        let n = keys.len();
+        ring_after_removal.remove(&keys[n - 2..], &[n - 2, n - 1], srs);
+        let same_ring = TestRing::with_keys(&piop_params, &keys[..n - 2], &ring_builder_key);
+        assert_eq!(ring_after_removal, same_ring);
+        let mut ring_after_removal = ring.clone();
+        ring_after_removal.remove(&[keys[n - 1], keys[n - 2]], &[n - 1, n - 2], srs);
+        assert_eq!(ring_after_removal, same_ring);
+
+        // Removing a key in the middle leaves the padding point in its position.
+        let mut ring_after_removal = ring.clone();
+        ring_after_removal.remove(&keys[1..2], &[1], srs);
+        let mut padded_keys = keys.clone();
+        padded_keys[1] = piop_params.padding_point;
+        let same_ring = TestRing::with_keys(&piop_params, &padded_keys, &ring_builder_key);
+        assert_eq!(ring_after_removal, same_ring);
+
+        let mut keys = keys;
+        let new_key = SWAffine::rand(rng);
+        let i = keys.len() / 2;
+        ring.rekey_at(i, keys[i], new_key, ring_builder_key.lis_in_g1[i]);
+        keys[i] = new_key;
+        let same_ring = TestRing::with_keys(&piop_params, &keys, &ring_builder_key);
+        assert_eq!(ring, same_ring);
+    }
+
+    #[test]
+    #[should_panic(expected = "duplicate positions")]
+    fn test_remove_duplicate_positions() {
+        let rng = &mut test_rng();
+
+        let domain_size = 1 << 9;
+        let pcs_params = KZG::<Bls12_381>::setup(domain_size - 1, rng);
+        let ring_builder_key = RingBuilderKey::from_srs(&pcs_params, domain_size).unwrap();
+        let srs = |range: Range<usize>| Ok(ring_builder_key.lis_in_g1[range].to_vec());
+        let h = SWAffine::rand(rng);
+        let seed = SWAffine::rand(rng);
+        let piop_params = PiopParams::setup(Domain::new(domain_size, true), h, seed);
+
+        let keys = random_vec::<SWAffine, _>(10, rng);
+        let mut ring = TestRing::with_keys(&piop_params, &keys, &ring_builder_key);
+        ring.remove(&[keys[9], keys[9]], &[9, 9], srs);
+    }
+
+    #[test]
+    fn test_key_commitments() {
+        let rng = &mut test_rng();
+
+        let domain_size = 1 << 9;
+        let pcs_params = KZG::<Bls12_381>::setup(domain_size - 1, rng);
+        let ring_builder_key = RingBuilderKey::from_srs(&pcs_params, domain_size).unwrap();
+        let h = SWAffine::rand(rng);
+        let seed = SWAffine::rand(rng);
+        let piop_params = PiopParams::setup(Domain::new(domain_size, true), h, seed);
+
+        let keys = random_vec::<SWAffine, _>(10, rng);
+        let key_commitments = TestRing::key_commitments(&piop_params, &keys, &ring_builder_key);
+        assert_eq!(key_commitments.len(), keys.len());
+
+        // Replacing the padding with the keys in the empty ring gives the ring with the keys.
+        let empty_ring = TestRing::with_keys(&piop_params, &[], &ring_builder_key);
+        let ring = TestRing::with_keys(&piop_params, &keys, &ring_builder_key);
+        let (padding_x, padding_y) = piop_params.padding_point.xy().unwrap();
+        let lis = ring_builder_key.lis_in_g1[..keys.len()]
+            .iter()
+            .sum::<<Bls12_381 as Pairing>::G1>();
+        let cx = key_commitments
+            .iter()
+            .map(|c| c.0)
+            .sum::<<Bls12_381 as Pairing>::G1>();
+        let cy = key_commitments
+            .iter()
+            .map(|c| c.1)
+            .sum::<<Bls12_381 as Pairing>::G1>();
+        assert_eq!(empty_ring.cx + cx - lis * padding_x, ring.cx);
+        assert_eq!(empty_ring.cy + cy - lis * padding_y, ring.cy);
     }
 
     #[test]
@@ -309,7 +532,7 @@ mod tests {
         let domain_size = 1 << 9;
 
         let pcs_params = KZG::<Bls12_381>::setup(domain_size - 1, rng);
-        let ring_builder_key = RingBuilderKey::from_srs(&pcs_params, domain_size);
+        let ring_builder_key = RingBuilderKey::from_srs(&pcs_params, domain_size).unwrap();
         let srs = |range: Range<usize>| Ok(ring_builder_key.lis_in_g1[range].to_vec());
 
         // piop params
@@ -323,6 +546,38 @@ mod tests {
         assert_eq!(ring, same_ring);
     }
 
+    #[test]
+    fn test_ring_serialization() {
+        let rng = &mut test_rng();
+
+        let domain_size = 1 << 9;
+        let pcs_params = KZG::<Bls12_381>::setup(domain_size - 1, rng);
+        let ring_builder_key = RingBuilderKey::from_srs(&pcs_params, domain_size).unwrap();
+        let h = SWAffine::rand(rng);
+        let seed = SWAffine::rand(rng);
+        let piop_params = PiopParams::setup(Domain::new(domain_size, true), h, seed);
+
+        let keys = random_vec::<SWAffine, _>(10, rng);
+        let ring = TestRing::with_keys(&piop_params, &keys, &ring_builder_key);
+        let mut buf = vec![];
+        ring.serialize_compressed(&mut buf).unwrap();
+        let res = TestRing::deserialize_compressed(&buf[..]).unwrap();
+        assert_eq!(res, ring);
+        assert_eq!(res.domain_size, domain_size);
+    }
+
+    #[test]
+    fn test_oversized_domain() {
+        let rng = &mut test_rng();
+        let domain_size = 1 << 9;
+        let pcs_params = KZG::<Bls12_381>::setup(domain_size - 1, rng);
+        assert!(RingBuilderKey::<Fr, Bls12_381>::from_srs(&pcs_params, domain_size).is_ok());
+        assert_eq!(
+            RingBuilderKey::<Fr, Bls12_381>::from_srs(&pcs_params, 2 * domain_size).err(),
+            Some(SrsError::DomainSizeExceedsSrs)
+        );
+    }
+
     fn get_monomial_commitment(
         pcs_params: &URS<Bls12_381>,
         piop_params: &PiopParams<Fr, BandersnatchConfig>,
@@ -1,13 +1,14 @@
 use ark_ec::short_weierstrass::{Affine, SWCurveConfig};
 use ark_ff::PrimeField;
+use ark_std::rand::RngCore;
 use fflonk::pcs::PCS;
 
 use common::prover::PlonkProver;
 use common::transcript::PlonkTranscript;
 
 use crate::piop::params::PiopParams;
-use crate::piop::{FixedColumns, PiopProver, ProverKey};
-use crate::RingProof;
+use crate::piop::{FixedColumns, PiopProver, ProverKey, VerifierKey};
+use crate::{BuilderError, RingProof};
 
 pub struct RingProver<F, CS, Curve, T>
 where
@@ -19,6 +20,8 @@ where
     piop_params: PiopParams<F, Curve>,
     fixed_columns: FixedColumns<F, Affine<Curve>>,
     k: usize,
+    // Kept to set up the matching verifier.
+    verifier_key: VerifierKey<F, CS>,
     plonk_prover: PlonkProver<F, CS, T>,
 }
 
@@ -29,11 +32,25 @@ where
     Curve: SWCurveConfig<BaseField = F>,
     T: PlonkTranscript<F, CS>,
 {
+    pub fn builder() -> RingProverBuilder<F, CS, Curve, T> {
+        RingProverBuilder::default()
+    }
+
+    #[deprecated(note = "use `RingProver::builder`")]
     pub fn init(
         prover_key: ProverKey<F, CS, Affine<Curve>>,
         piop_params: PiopParams<F, Curve>,
         k: usize,
         empty_transcript: T,
+    ) -> Self {
+        Self::new(prover_key, piop_params, k, empty_transcript)
+    }
+
+    fn new(
+        prover_key: ProverKey<F, CS, Affine<Curve>>,
+        piop_params: PiopParams<F, Curve>,
+        k: usize,
+        empty_transcript: T,
     ) -> Self {
         let ProverKey {
             pcs_ck,
@@ -41,22 +58,171 @@ where
             verifier_key,
         } = prover_key;
 
-        let plonk_prover = PlonkProver::init(pcs_ck, verifier_key, empty_transcript);
+        let plonk_prover = PlonkProver::init(pcs_ck, &verifier_key, empty_transcript);
 
         Self {
             piop_params,
             fixed_columns,
             k,
+            verifier_key,
             plonk_prover,
         }
     }
 
     pub fn prove(&self, t: Curve::ScalarField) -> RingProof<F, CS> {
-        let piop = PiopProver::build(&self.piop_params, self.fixed_columns.clone(), self.k, t);
+        self.prove_with_external_randomness(t, &mut getrandom_or_panic::getrandom_or_panic())
+    }
+
+    // Same as `prove`, but the private columns are blinded with the randomness from `rng`,
+    // e.g. generated by a hardware device, instead of the system randomness.
+    pub fn prove_with_external_randomness<R: RngCore>(
+        &self,
+        t: Curve::ScalarField,
+        rng: &mut R,
+    ) -> RingProof<F, CS> {
+        self.prove_bound_to(t, &[], rng)
+    }
+
+    // Same as `prove`, but the proof is bound to the `extra_inputs`, e.g. the VRF input and output,
+    // and only verifies with `RingVerifier::verify_with_extra_inputs` for the same points in the same order.
+    pub fn prove_with_extra_inputs(
+        &self,
+        t: Curve::ScalarField,
+        extra_inputs: &[Affine<Curve>],
+    ) -> RingProof<F, CS> {
+        self.prove_bound_to(
+            t,
+            extra_inputs,
+            &mut getrandom_or_panic::getrandom_or_panic(),
+        )
+    }
+
+    fn prove_bound_to<R: RngCore>(
+        &self,
+        t: Curve::ScalarField,
+        extra_inputs: &[Affine<Curve>],
+        rng: &mut R,
+    ) -> RingProof<F, CS> {
+        let piop = PiopProver::build(
+            &self.piop_params,
+            self.fixed_columns.clone(),
+            self.k,
+            t,
+            rng,
+        )
+        .with_extra_inputs(extra_inputs.to_vec());
         self.plonk_prover.prove(piop)
     }
 
     pub fn piop_params(&self) -> &PiopParams<F, Curve> {
         &self.piop_params
     }
+
+    // Index of the prover's public key in the ring.
+    pub fn key_index(&self) -> usize {
+        self.k
+    }
+
+    pub fn verifier_key(&self) -> &VerifierKey<F, CS> {
+        &self.verifier_key
+    }
+}
+
+#[cfg(feature = "async")]
+impl<F, CS, Curve, T> RingProver<F, CS, Curve, T>
+where
+    F: PrimeField,
+    CS: PCS<F> + 'static,
+    Curve: SWCurveConfig<BaseField = F>,
+    T: PlonkTranscript<F, CS> + Send + 'static,
+    Self: Send,
+    RingProof<F, CS>: Send,
+{
+    // Runs the prover on the blocking thread pool of the tokio runtime, not to block the executor.
+    pub async fn prove_async(
+        self,
+        t: Curve::ScalarField,
+    ) -> Result<RingProof<F, CS>, crate::ProverError> {
+        tokio::task::spawn_blocking(move || self.prove(t))
+            .await
+            .map_err(crate::ProverError::SpawnFailed)
+    }
+}
+
+// Collects the parameters of the prover, that may become known at different times.
+pub struct RingProverBuilder<F, CS, Curve, T>
+where
+    F: PrimeField,
+    CS: PCS<F>,
+    Curve: SWCurveConfig<BaseField = F>,
+    T: PlonkTranscript<F, CS>,
+{
+    prover_key: Option<ProverKey<F, CS, Affine<Curve>>>,
+    piop_params: Option<PiopParams<F, Curve>>,
+    k: Option<usize>,
+    empty_transcript: Option<T>,
+}
+
+impl<F, CS, Curve, T> Default for RingProverBuilder<F, CS, Curve, T>
+where
+    F: PrimeField,
+    CS: PCS<F>,
+    Curve: SWCurveConfig<BaseField = F>,
+    T: PlonkTranscript<F, CS>,
+{
+    fn default() -> Self {
+        Self {
+            prover_key: None,
+            piop_params: None,
+            k: None,
+            empty_transcript: None,
+        }
+    }
+}
+
+impl<F, CS, Curve, T> RingProverBuilder<F, CS, Curve, T>
+where
+    F: PrimeField,
+    CS: PCS<F>,
+    Curve: SWCurveConfig<BaseField = F>,
+    T: PlonkTranscript<F, CS>,
+{
+    pub fn with_prover_key(mut self, prover_key: ProverKey<F, CS, Affine<Curve>>) -> Self {
+        self.prover_key = Some(prover_key);
+        self
+    }
+
+    pub fn with_piop_params(mut self, piop_params: PiopParams<F, Curve>) -> Self {
+        self.piop_params = Some(piop_params);
+        self
+    }
+
+    // Index of the prover's public key in the ring.
+    pub fn with_index(mut self, k: usize) -> Self {
+        self.k = Some(k);
+        self
+    }
+
+    pub fn with_transcript(mut self, empty_transcript: T) -> Self {
+        self.empty_transcript = Some(empty_transcript);
+        self
+    }
+
+    pub fn build(self) -> Result<RingProver<F, CS, Curve, T>, BuilderError> {
+        let prover_key = self.prover_key.ok_or(BuilderError::MissingProverKey)?;
+        let piop_params = self.piop_params.ok_or(BuilderError::MissingPiopParams)?;
+        let k = self.k.ok_or(BuilderError::MissingIndex)?;
+        let empty_transcript = self
+            .empty_transcript
+            .ok_or(BuilderError::MissingTranscript)?;
+        if k >= piop_params.keyset_part_size {
+            return Err(BuilderError::IndexOutOfRange);
+        }
+        Ok(RingProver::new(
+            prover_key,
+            piop_params,
+            k,
+            empty_transcript,
+        ))
+    }
 }
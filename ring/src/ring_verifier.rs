@@ -1,16 +1,20 @@
 use ark_ec::short_weierstrass::{Affine, SWCurveConfig};
-use ark_ec::CurveGroup;
+use ark_ec::{AffineRepr, CurveGroup};
 use ark_ff::PrimeField;
+use ark_poly::EvaluationDomain;
+use ark_std::rand::Rng;
+use ark_std::vec::Vec;
 use fflonk::pcs::{RawVerifierKey, PCS};
 
-use common::domain::EvaluatedDomain;
 use common::piop::VerifierPiop;
 use common::transcript::PlonkTranscript;
-use common::verifier::PlonkVerifier;
+use common::verifier::{Challenges, PlonkVerifier};
 
 use crate::piop::params::PiopParams;
 use crate::piop::{FixedColumnsCommitted, PiopVerifier, VerifierKey};
-use crate::RingProof;
+use crate::{
+    BuilderError, RingCommitments, RingEvaluations, RingOpenings, RingProof, RingProofError,
+};
 
 pub struct RingVerifier<F, CS, Curve, T>
 where
@@ -21,6 +25,8 @@ where
 {
     piop_params: PiopParams<F, Curve>,
     fixed_columns_committed: FixedColumnsCommitted<F, CS::C>,
+    // Size of the domain the verifier key was computed for.
+    domain_size: usize,
     plonk_verifier: PlonkVerifier<F, CS, T>,
 }
 
@@ -31,50 +37,340 @@ where
     Curve: SWCurveConfig<BaseField = F>,
     T: PlonkTranscript<F, CS>,
 {
+    pub fn builder() -> RingVerifierBuilder<F, CS, Curve, T> {
+        RingVerifierBuilder::default()
+    }
+
+    #[deprecated(note = "use `RingVerifier::builder`")]
     pub fn init(
         verifier_key: VerifierKey<F, CS>,
         piop_params: PiopParams<F, Curve>,
         empty_transcript: T,
+    ) -> Self {
+        Self::new(verifier_key, piop_params, empty_transcript)
+    }
+
+    fn new(
+        verifier_key: VerifierKey<F, CS>,
+        piop_params: PiopParams<F, Curve>,
+        empty_transcript: T,
     ) -> Self {
         let pcs_vk = verifier_key.pcs_raw_vk.prepare();
         let plonk_verifier = PlonkVerifier::init(pcs_vk, &verifier_key, empty_transcript);
         Self {
             piop_params,
             fixed_columns_committed: verifier_key.fixed_columns_committed,
+            domain_size: verifier_key.domain_size,
             plonk_verifier,
         }
     }
 
     pub fn verify_ring_proof(&self, proof: RingProof<F, CS>, result: Affine<Curve>) -> bool {
+        self.verify_returning_error(proof, result).is_ok()
+    }
+
+    // Same as `verify_ring_proof`, but tells why the proof was rejected.
+    pub fn verify_returning_error(
+        &self,
+        proof: RingProof<F, CS>,
+        result: Affine<Curve>,
+    ) -> Result<(), RingProofError> {
+        self.verify_with_extra_inputs(proof, result, &[])
+    }
+
+    // Verifies the proof produced with `RingProver::prove_with_extra_inputs` for the `extra_inputs`.
+    pub fn verify_with_extra_inputs(
+        &self,
+        proof: RingProof<F, CS>,
+        result: Affine<Curve>,
+        extra_inputs: &[Affine<Curve>],
+    ) -> Result<(), RingProofError> {
+        if self.domain_size != self.piop_params.domain.domain().size() {
+            return Err(RingProofError::DomainMismatch);
+        }
+        if proof.is_trivial() {
+            return Err(RingProofError::MalformedProof);
+        }
+        let public_inputs = [&[result], extra_inputs].concat();
         let (challenges, mut rng) = self.plonk_verifier.restore_challenges(
-            &result,
+            &public_inputs,
             &proof,
             // '1' accounts for the quotient polynomial that is aggregated together with the columns
             PiopVerifier::<F, CS::C>::N_COLUMNS + 1,
             PiopVerifier::<F, CS::C>::N_CONSTRAINTS,
         );
+        if self.verify_with_challenges(proof, result, challenges, &mut rng) {
+            Ok(())
+        } else {
+            Err(RingProofError::KzgVerificationFailed)
+        }
+    }
+
+    // Verifies the proof, the column evaluations of which are received separately from the commitments,
+    // in the evaluation point `challenge_zeta` supplied by the caller instead of the one derived from the transcript.
+    // The other challenges are still restored from the transcript,
+    // as the quotient and the aggregated opening depend on them.
+    // As it bypasses Fiat-Shamir for `zeta`, it is unsound and should not be used beyond benchmarking.
+    // Returns `None` if the proof is malformed, or `challenge_zeta` is in the domain.
+    #[deprecated(note = "only for benchmarking")]
+    pub fn verify_precomputed_evaluations(
+        &self,
+        proof_commitments: RingCommitments<F, CS::C>,
+        evaluations: RingEvaluations<F>,
+        vrf_output: Affine<Curve>,
+        challenge_zeta: F,
+        openings: RingOpenings<F, CS>,
+    ) -> Option<bool> {
+        let domain = self.piop_params.domain.domain();
+        if self.domain_size != domain.size()
+            || vrf_output.is_zero()
+            || domain
+                .evaluate_vanishing_polynomial(challenge_zeta)
+                .is_zero()
+        {
+            return None;
+        }
+        let proof = RingProof {
+            column_commitments: proof_commitments,
+            columns_at_zeta: evaluations,
+            quotient_commitment: openings.quotient_commitment,
+            lin_at_zeta_omega: openings.lin_at_zeta_omega,
+            agg_at_zeta_proof: openings.agg_at_zeta_proof,
+            lin_at_zeta_omega_proof: openings.lin_at_zeta_omega_proof,
+        };
+        if proof.is_trivial() {
+            return None;
+        }
+        let (mut challenges, mut rng) = self.plonk_verifier.restore_challenges(
+            &[vrf_output],
+            &proof,
+            PiopVerifier::<F, CS::C>::N_COLUMNS + 1,
+            PiopVerifier::<F, CS::C>::N_CONSTRAINTS,
+        );
+        challenges.zeta = challenge_zeta;
+        Some(self.verify_with_challenges(proof, vrf_output, challenges, &mut rng))
+    }
+
+    // Verifies the proofs together, that is faster than one by one, as the openings are checked at once.
+    // Unlike `verify_ring_proof`, the randomness to combine the openings comes from `rng`,
+    // as it should be unpredictable to the provers of all the proofs.
+    pub fn verify_batch<R: Rng>(
+        &self,
+        proofs: Vec<(RingProof<F, CS>, Affine<Curve>)>,
+        rng: &mut R,
+    ) -> bool {
+        if self.domain_size != self.piop_params.domain.domain().size()
+            || proofs.iter().any(|(proof, _)| proof.is_trivial())
+        {
+            return false;
+        }
+        let instances = proofs.into_iter().map(|(proof, result)| {
+            let (challenges, _) = self.plonk_verifier.restore_challenges(
+                &[result],
+                &proof,
+                PiopVerifier::<F, CS::C>::N_COLUMNS + 1,
+                PiopVerifier::<F, CS::C>::N_CONSTRAINTS,
+            );
+            let piop = self.piop(&proof, result, challenges.zeta);
+            (piop, proof, challenges)
+        });
+        self.plonk_verifier.verify_batch(instances, rng)
+    }
+
+    fn verify_with_challenges<R: Rng>(
+        &self,
+        proof: RingProof<F, CS>,
+        result: Affine<Curve>,
+        challenges: Challenges<F>,
+        rng: &mut R,
+    ) -> bool {
+        let piop = self.piop(&proof, result, challenges.zeta);
+        self.plonk_verifier.verify(piop, proof, challenges, rng)
+    }
+
+    fn piop(
+        &self,
+        proof: &RingProof<F, CS>,
+        result: Affine<Curve>,
+        zeta: F,
+    ) -> PiopVerifier<F, CS::C> {
         let seed = self.piop_params.seed;
         let seed_plus_result = (seed + result).into_affine();
-        let domain_eval = EvaluatedDomain::new(
-            self.piop_params.domain.domain(),
-            challenges.zeta,
-            self.piop_params.domain.hiding,
-        );
+        let domain_eval = self.piop_params.domain.evaluate(zeta);
 
-        let piop = PiopVerifier::init(
+        PiopVerifier::init(
             domain_eval,
             self.fixed_columns_committed.clone(),
             proof.column_commitments.clone(),
             proof.columns_at_zeta.clone(),
             (seed.x, seed.y),
             (seed_plus_result.x, seed_plus_result.y),
-        );
-
-        self.plonk_verifier
-            .verify(piop, proof, challenges, &mut rng)
+        )
     }
 
     pub fn piop_params(&self) -> &PiopParams<F, Curve> {
         &self.piop_params
     }
 }
+
+pub struct RingVerifierBuilder<F, CS, Curve, T>
+where
+    F: PrimeField,
+    CS: PCS<F>,
+    Curve: SWCurveConfig<BaseField = F>,
+    T: PlonkTranscript<F, CS>,
+{
+    verifier_key: Option<VerifierKey<F, CS>>,
+    piop_params: Option<PiopParams<F, Curve>>,
+    empty_transcript: Option<T>,
+}
+
+impl<F, CS, Curve, T> Default for RingVerifierBuilder<F, CS, Curve, T>
+where
+    F: PrimeField,
+    CS: PCS<F>,
+    Curve: SWCurveConfig<BaseField = F>,
+    T: PlonkTranscript<F, CS>,
+{
+    fn default() -> Self {
+        Self {
+            verifier_key: None,
+            piop_params: None,
+            empty_transcript: None,
+        }
+    }
+}
+
+impl<F, CS, Curve, T> RingVerifierBuilder<F, CS, Curve, T>
+where
+    F: PrimeField,
+    CS: PCS<F>,
+    Curve: SWCurveConfig<BaseField = F>,
+    T: PlonkTranscript<F, CS>,
+{
+    pub fn with_verifier_key(mut self, verifier_key: VerifierKey<F, CS>) -> Self {
+        self.verifier_key = Some(verifier_key);
+        self
+    }
+
+    pub fn with_piop_params(mut self, piop_params: PiopParams<F, Curve>) -> Self {
+        self.piop_params = Some(piop_params);
+        self
+    }
+
+    pub fn with_transcript(mut self, empty_transcript: T) -> Self {
+        self.empty_transcript = Some(empty_transcript);
+        self
+    }
+
+    pub fn build(self) -> Result<RingVerifier<F, CS, Curve, T>, BuilderError> {
+        let verifier_key = self.verifier_key.ok_or(BuilderError::MissingVerifierKey)?;
+        let piop_params = self.piop_params.ok_or(BuilderError::MissingPiopParams)?;
+        let empty_transcript = self
+            .empty_transcript
+            .ok_or(BuilderError::MissingTranscript)?;
+        Ok(RingVerifier::new(
+            verifier_key,
+            piop_params,
+            empty_transcript,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_bls12_381::Bls12_381;
+    use ark_ed_on_bls12_381_bandersnatch::{BandersnatchConfig, Fq, Fr, SWAffine};
+    use ark_ff::One;
+    use ark_std::{test_rng, UniformRand};
+    use fflonk::pcs::kzg::KZG;
+
+    use common::test_helpers::random_vec;
+
+    use crate::ring_prover::RingProver;
+    use crate::{find_complement_point, index, ArkTranscript, Domain};
+
+    use super::*;
+
+    type TestVerifier = RingVerifier<Fq, KZG<Bls12_381>, BandersnatchConfig, ArkTranscript>;
+
+    // A valid proof, the result it's valid for, and the verifier.
+    fn proof_and_verifier() -> (RingProof<Fq, KZG<Bls12_381>>, SWAffine, TestVerifier) {
+        let rng = &mut test_rng();
+        let domain_size = 2usize.pow(9);
+        let pcs_params = KZG::<Bls12_381>::setup(3 * domain_size, rng);
+        let domain = Domain::new(domain_size, true);
+        let piop_params = PiopParams::setup(domain, SWAffine::rand(rng), find_complement_point());
+        let pks = random_vec::<SWAffine, _>(10, rng);
+        let (prover_key, verifier_key) =
+            index::<_, KZG<Bls12_381>, _>(&pcs_params, &piop_params, &pks);
+
+        let secret = Fr::rand(rng);
+        let result = (piop_params.h * secret + pks[0]).into_affine();
+        let ring_prover = RingProver::builder()
+            .with_prover_key(prover_key)
+            .with_piop_params(piop_params.clone())
+            .with_index(0)
+            .with_transcript(ArkTranscript::new(b"ring-vrf-test"))
+            .build()
+            .unwrap();
+        let ring_verifier = RingVerifier::builder()
+            .with_verifier_key(verifier_key)
+            .with_piop_params(piop_params)
+            .with_transcript(ArkTranscript::new(b"ring-vrf-test"))
+            .build()
+            .unwrap();
+        (ring_prover.prove(secret), result, ring_verifier)
+    }
+
+    fn challenges(
+        ring_verifier: &TestVerifier,
+        proof: &RingProof<Fq, KZG<Bls12_381>>,
+        result: SWAffine,
+    ) -> Challenges<Fq> {
+        let (challenges, _) = ring_verifier.plonk_verifier.restore_challenges(
+            &[result],
+            proof,
+            PiopVerifier::<_, <KZG<Bls12_381> as PCS<_>>::C>::N_COLUMNS + 1,
+            PiopVerifier::<_, <KZG<Bls12_381> as PCS<_>>::C>::N_CONSTRAINTS,
+        );
+        challenges
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_verify_precomputed_evaluations() {
+        let (proof, result, ring_verifier) = proof_and_verifier();
+        let zeta = challenges(&ring_verifier, &proof, result).zeta;
+        let verify = |evaluations: RingEvaluations<_>, zeta| {
+            let proof = proof.clone();
+            let openings = RingOpenings {
+                quotient_commitment: proof.quotient_commitment,
+                lin_at_zeta_omega: proof.lin_at_zeta_omega,
+                agg_at_zeta_proof: proof.agg_at_zeta_proof,
+                lin_at_zeta_omega_proof: proof.lin_at_zeta_omega_proof,
+            };
+            ring_verifier.verify_precomputed_evaluations(
+                proof.column_commitments,
+                evaluations,
+                result,
+                zeta,
+                openings,
+            )
+        };
+
+        assert_eq!(verify(proof.columns_at_zeta.clone(), zeta), Some(true));
+        let mut tampered = proof.columns_at_zeta.clone();
+        tampered.bits += Fq::from(1u8);
+        assert_eq!(verify(tampered, zeta), Some(false));
+        assert_eq!(verify(proof.columns_at_zeta.clone(), Fq::one()), None);
+    }
+
+    #[test]
+    fn test_zeta_in_domain() {
+        let (proof, result, ring_verifier) = proof_and_verifier();
+        let mut challenges = challenges(&ring_verifier, &proof, result);
+        challenges.zeta = ring_verifier.piop_params.domain.omega();
+        assert!(!ring_verifier.verify_with_challenges(proof, result, challenges, &mut test_rng()));
+    }
+}
@@ -0,0 +1,239 @@
+use ark_ec::short_weierstrass::{Affine, SWCurveConfig};
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ff::PrimeField;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::marker::PhantomData;
+use ark_std::rand::RngCore;
+use ark_std::vec::Vec;
+use ark_std::UniformRand;
+use fflonk::pcs::PCS;
+
+use common::transcript::PlonkTranscript;
+
+use crate::ring_prover::RingProver;
+use crate::ring_verifier::RingVerifier;
+use crate::RingProof;
+
+// Proof of knowledge of `sk` and `t` such that `blinded_pk = sk.G + t.H` and `vrf_out = sk.vrf_in`,
+// where `G` is the generator of the curve, and `H` is the blinding base from the piop parameters.
+#[derive(Clone, CanonicalSerialize, CanonicalDeserialize)]
+pub struct DleqProof<Curve: SWCurveConfig> {
+    // `k.G + k_t.H`
+    pub r: Affine<Curve>,
+    // `k.vrf_in`
+    pub r_out: Affine<Curve>,
+    // `k + c.sk`
+    pub s: Curve::ScalarField,
+    // `k_t + c.t`
+    pub s_t: Curve::ScalarField,
+}
+
+#[derive(CanonicalSerialize, CanonicalDeserialize)]
+pub struct CombinedProof<F: PrimeField, CS: PCS<F>, Curve: SWCurveConfig<BaseField = F>> {
+    // Proves that `blinded_pk - t.H` is in the ring.
+    pub ring_proof: RingProof<F, CS>,
+    pub blinded_pk: Affine<Curve>,
+    // Proves that the VRF output was computed with the secret key of the blinded public key.
+    pub dleq_proof: DleqProof<Curve>,
+}
+
+// Ring VRF: the ring proof and the DLEQ proof bound together.
+// The ring proof transcript binds the VRF input and output, and the message hashed to a point,
+// and the DLEQ challenge is derived from the ring proof and the same data.
+// The public keys in the ring are expected to be of the form `sk.G`, where `G` is the generator of the curve.
+pub struct PlonkVrf<F, CS, Curve>(PhantomData<(F, CS, Curve)>);
+
+impl<F, CS, Curve> PlonkVrf<F, CS, Curve>
+where
+    F: PrimeField,
+    CS: PCS<F>,
+    Curve: SWCurveConfig<BaseField = F>,
+{
+    // Returns the VRF output `sk.vrf_in` and the proof of its correctness by a member of the ring.
+    pub fn prove<T: PlonkTranscript<F, CS>, R: RngCore>(
+        ring_prover: &RingProver<F, CS, Curve, T>,
+        sk: Curve::ScalarField,
+        vrf_in: Affine<Curve>,
+        message: &[u8],
+        rng: &mut R,
+    ) -> (Affine<Curve>, CombinedProof<F, CS, Curve>) {
+        let g = Affine::<Curve>::generator();
+        let h = ring_prover.piop_params().h;
+        let t = Curve::ScalarField::rand(rng);
+        let blinded_pk = (g * sk + h * t).into_affine();
+        let vrf_out = (vrf_in * sk).into_affine();
+        let ring_proof =
+            ring_prover.prove_with_extra_inputs(t, &Self::extra_inputs(vrf_in, vrf_out, message));
+
+        let k = Curve::ScalarField::rand(rng);
+        let k_t = Curve::ScalarField::rand(rng);
+        let r = (g * k + h * k_t).into_affine();
+        let r_out = (vrf_in * k).into_affine();
+        let c = Self::challenge(
+            &ring_proof,
+            &blinded_pk,
+            &vrf_in,
+            &vrf_out,
+            &r,
+            &r_out,
+            message,
+        );
+        let dleq_proof = DleqProof {
+            r,
+            r_out,
+            s: k + c * sk,
+            s_t: k_t + c * t,
+        };
+
+        let proof = CombinedProof {
+            ring_proof,
+            blinded_pk,
+            dleq_proof,
+        };
+        (vrf_out, proof)
+    }
+
+    pub fn verify<T: PlonkTranscript<F, CS>>(
+        ring_verifier: &RingVerifier<F, CS, Curve, T>,
+        vrf_in: Affine<Curve>,
+        vrf_out: Affine<Curve>,
+        proof: CombinedProof<F, CS, Curve>,
+        message: &[u8],
+    ) -> bool {
+        let CombinedProof {
+            ring_proof,
+            blinded_pk,
+            dleq_proof: DleqProof { r, r_out, s, s_t },
+        } = proof;
+        let g = Affine::<Curve>::generator();
+        let h = ring_verifier.piop_params().h;
+        let c = Self::challenge(
+            &ring_proof,
+            &blinded_pk,
+            &vrf_in,
+            &vrf_out,
+            &r,
+            &r_out,
+            message,
+        );
+        let dleq_valid = g * s + h * s_t == r + blinded_pk * c && vrf_in * s == r_out + vrf_out * c;
+        dleq_valid
+            && ring_verifier
+                .verify_with_extra_inputs(
+                    ring_proof,
+                    blinded_pk,
+                    &Self::extra_inputs(vrf_in, vrf_out, message),
+                )
+                .is_ok()
+    }
+
+    // The public inputs the ring proof is bound to in addition to the blinded public key.
+    fn extra_inputs(
+        vrf_in: Affine<Curve>,
+        vrf_out: Affine<Curve>,
+        message: &[u8],
+    ) -> [Affine<Curve>; 3] {
+        [vrf_in, vrf_out, crate::hash_to_curve(message)]
+    }
+
+    fn challenge(
+        ring_proof: &RingProof<F, CS>,
+        blinded_pk: &Affine<Curve>,
+        vrf_in: &Affine<Curve>,
+        vrf_out: &Affine<Curve>,
+        r: &Affine<Curve>,
+        r_out: &Affine<Curve>,
+        message: &[u8],
+    ) -> Curve::ScalarField {
+        let mut transcript = ark_transcript::Transcript::new_labeled(b"w3f-ring-proof-plonk-vrf");
+        transcript.label(b"ring_proof");
+        transcript.append(ring_proof);
+        transcript.label(b"blinded_pk");
+        transcript.append(blinded_pk);
+        transcript.label(b"vrf_io");
+        transcript.append(vrf_in);
+        transcript.append(vrf_out);
+        transcript.label(b"dleq_commitments");
+        transcript.append(r);
+        transcript.append(r_out);
+        transcript.label(b"message");
+        transcript.write_bytes(message);
+        transcript.challenge(b"dleq_challenge").read_reduce()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_bls12_381::Bls12_381;
+    use ark_ed_on_bls12_381_bandersnatch::{BandersnatchConfig, Fr, SWAffine};
+    use ark_std::rand::Rng;
+    use ark_std::{test_rng, UniformRand};
+    use fflonk::pcs::kzg::KZG;
+
+    use common::test_helpers::random_vec;
+
+    use crate::ring_prover::RingProver;
+    use crate::ring_verifier::RingVerifier;
+    use crate::{find_complement_point, index, ArkTranscript, Domain, PiopParams};
+
+    use super::*;
+
+    #[test]
+    fn test_plonk_vrf() {
+        let rng = &mut test_rng();
+        let domain_size = 2usize.pow(9);
+        let pcs_params = KZG::<Bls12_381>::setup(3 * domain_size, rng);
+        let domain = Domain::new(domain_size, true);
+        let h = SWAffine::rand(rng);
+        let seed = find_complement_point::<BandersnatchConfig>();
+        let piop_params = PiopParams::setup(domain, h, seed);
+
+        let sk = Fr::rand(rng);
+        let mut pks = random_vec::<SWAffine, _>(10, rng);
+        let k = rng.gen_range(0..pks.len());
+        pks[k] = (SWAffine::generator() * sk).into_affine();
+        let (prover_key, verifier_key) =
+            index::<_, KZG<Bls12_381>, _>(&pcs_params, &piop_params, &pks);
+
+        let ring_prover = RingProver::builder()
+            .with_prover_key(prover_key)
+            .with_piop_params(piop_params.clone())
+            .with_index(k)
+            .with_transcript(ArkTranscript::new(b"plonk-vrf-test"))
+            .build()
+            .unwrap();
+        let ring_verifier = RingVerifier::builder()
+            .with_verifier_key(verifier_key)
+            .with_piop_params(piop_params)
+            .with_transcript(ArkTranscript::new(b"plonk-vrf-test"))
+            .build()
+            .unwrap();
+
+        let vrf_in = SWAffine::rand(rng);
+        let verify =
+            |vrf_out, proof| PlonkVrf::verify(&ring_verifier, vrf_in, vrf_out, proof, b"message");
+        let (vrf_out, proof) = PlonkVrf::prove(&ring_prover, sk, vrf_in, b"message", rng);
+        assert_eq!(vrf_out, (vrf_in * sk).into_affine());
+        assert!(verify(vrf_out, proof));
+
+        let (vrf_out, proof) = PlonkVrf::prove(&ring_prover, sk, vrf_in, b"message", rng);
+        assert!(!PlonkVrf::verify(
+            &ring_verifier,
+            vrf_in,
+            vrf_out,
+            proof,
+            b"other message"
+        ));
+
+        let (_, proof) = PlonkVrf::prove(&ring_prover, sk, vrf_in, b"message", rng);
+        assert!(!verify(SWAffine::rand(rng), proof));
+
+        let (vrf_out, mut proof) = PlonkVrf::prove(&ring_prover, sk, vrf_in, b"message", rng);
+        proof.blinded_pk = SWAffine::rand(rng);
+        assert!(!verify(vrf_out, proof));
+
+        let (vrf_out, mut proof) = PlonkVrf::prove(&ring_prover, sk, vrf_in, b"message", rng);
+        proof.dleq_proof.s += Fr::from(1u8);
+        assert!(!verify(vrf_out, proof));
+    }
+}
@@ -0,0 +1,90 @@
+#![cfg(feature = "stress-testing")]
+
+use std::sync::OnceLock;
+
+use ark_bls12_381::Bls12_381;
+use ark_ec::CurveGroup;
+use ark_ed_on_bls12_381_bandersnatch::{BandersnatchConfig, Fq, Fr, SWAffine};
+use ark_ff::PrimeField;
+use ark_std::rand::rngs::StdRng;
+use ark_std::rand::SeedableRng;
+use ark_std::UniformRand;
+use proptest::prelude::*;
+
+use common::test_helpers::random_vec;
+use ring::pcs::kzg::KZG;
+use ring::pcs::PCS;
+use ring::ring_prover::RingProver;
+use ring::ring_verifier::RingVerifier;
+use ring::{find_complement_point, index, ArkTranscript, Domain, PiopParams, RingProof};
+
+type CS = KZG<Bls12_381>;
+
+const MAX_DOMAIN_SIZE: usize = 2048;
+
+// The SRS is shared by all the cases, as generating it dominates the running time.
+fn pcs_params() -> &'static <CS as PCS<Fq>>::Params {
+    static PCS_PARAMS: OnceLock<<CS as PCS<Fq>>::Params> = OnceLock::new();
+    PCS_PARAMS.get_or_init(|| CS::setup(3 * MAX_DOMAIN_SIZE, &mut StdRng::seed_from_u64(0)))
+}
+
+// (domain size, keyset size, prover's index)
+fn ring_setups() -> impl Strategy<Value = (usize, usize, usize)> {
+    prop_oneof![Just(512usize), Just(1024), Just(MAX_DOMAIN_SIZE)]
+        .prop_flat_map(|domain_size| {
+            // the same as `PiopParams::keyset_part_size`
+            let capacity = Domain::<Fq>::new(domain_size, true).capacity;
+            let max_keyset_size = capacity - Fr::MODULUS_BIT_SIZE as usize - 1;
+            (Just(domain_size), 1..=max_keyset_size)
+        })
+        .prop_flat_map(|(domain_size, keyset_size)| {
+            (Just(domain_size), Just(keyset_size), 0..keyset_size)
+        })
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(16))]
+
+    #[test]
+    fn prove_verify_round_trip(
+        (domain_size, keyset_size, k) in ring_setups(),
+        seed in any::<u64>(),
+        bit in any::<prop::sample::Index>(),
+    ) {
+        let rng = &mut StdRng::seed_from_u64(seed);
+        let domain = Domain::new(domain_size, true);
+        let h = SWAffine::rand(rng);
+        let piop_params = PiopParams::setup(domain, h, find_complement_point::<BandersnatchConfig>());
+        prop_assert!(keyset_size <= piop_params.keyset_part_size);
+
+        let pks = random_vec::<SWAffine, _>(keyset_size, rng);
+        let (prover_key, verifier_key) = index::<_, CS, _>(pcs_params(), &piop_params, &pks);
+
+        let secret = Fr::rand(rng);
+        let result = (h * secret + pks[k]).into_affine();
+        let ring_prover = RingProver::builder()
+            .with_prover_key(prover_key)
+            .with_piop_params(piop_params.clone())
+            .with_index(k)
+            .with_transcript(ArkTranscript::new(b"stress-test"))
+            .build()
+            .unwrap();
+        let ring_verifier = RingVerifier::builder()
+            .with_verifier_key(verifier_key)
+            .with_piop_params(piop_params)
+            .with_transcript(ArkTranscript::new(b"stress-test"))
+            .build()
+            .unwrap();
+
+        let proof = ring_prover.prove(secret);
+        let mut bytes = proof.to_compressed_bytes();
+        prop_assert!(ring_verifier.verify_ring_proof(proof, result));
+
+        // A tampered proof either fails to deserialize or to verify.
+        let i = bit.index(8 * bytes.len());
+        bytes[i / 8] ^= 1 << (i % 8);
+        if let Ok(proof) = RingProof::<Fq, CS>::from_compressed_bytes(&bytes) {
+            prop_assert!(!ring_verifier.verify_ring_proof(proof, result));
+        }
+    }
+}